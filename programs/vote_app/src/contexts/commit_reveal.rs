@@ -0,0 +1,140 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Mint, Token, TokenAccount};
+
+use crate::errors::VoteError;
+use crate::state::*;
+
+#[derive(Accounts)]
+#[instruction(proposal_id: u64)]
+pub struct CommitVote<'info> {
+    pub realm: Account<'info, Realm>,
+
+    #[account(seeds = [b"voter", realm.key().as_ref(), authority.key().as_ref()], bump)]
+    pub voter_account: Account<'info, Voter>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + VoteCommitment::INIT_SPACE,
+        seeds = [b"commitment", realm.key().as_ref(), proposal_id.to_be_bytes().as_ref(), authority.key().as_ref()],
+        bump
+    )]
+    pub commitment: Account<'info, VoteCommitment>,
+
+    #[account(seeds = [b"x_mint", realm.key().as_ref()], bump)]
+    pub x_mint: Account<'info, Mint>,
+
+    #[account(
+        mut,
+        constraint = voter_token_account.mint == x_mint.key() @ VoteError::TokenMintMismatch,
+        constraint = voter_token_account.owner == authority.key() @ VoteError::InvalidTokenAccountOwner
+    )]
+    pub voter_token_account: Account<'info, TokenAccount>,
+
+    #[account(mut, seeds = [b"proposal_vault", realm.key().as_ref(), proposal_id.to_be_bytes().as_ref()], bump)]
+    pub proposal_vault: Account<'info, TokenAccount>,
+
+    #[account(mut, seeds = [b"proposal", realm.key().as_ref(), proposal_id.to_be_bytes().as_ref()], bump)]
+    pub proposal_account: Account<'info, Proposal>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(proposal_id: u64)]
+pub struct RevealVote<'info> {
+    pub realm: Account<'info, Realm>,
+
+    #[account(mut, seeds = [b"voter", realm.key().as_ref(), authority.key().as_ref()], bump)]
+    pub voter_account: Account<'info, Voter>,
+
+    #[account(
+        mut,
+        seeds = [b"commitment", realm.key().as_ref(), proposal_id.to_be_bytes().as_ref(), authority.key().as_ref()],
+        bump
+    )]
+    pub commitment: Account<'info, VoteCommitment>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + VoteRecord::INIT_SPACE,
+        seeds = [b"vote", realm.key().as_ref(), proposal_id.to_be_bytes().as_ref(), authority.key().as_ref()],
+        bump
+    )]
+    pub vote_record: Account<'info, VoteRecord>,
+
+    #[account(seeds = [b"x_mint", realm.key().as_ref()], bump)]
+    pub x_mint: Account<'info, Mint>,
+
+    #[account(
+        mut,
+        constraint = voter_token_account.mint == x_mint.key() @ VoteError::TokenMintMismatch,
+        constraint = voter_token_account.owner == authority.key() @ VoteError::InvalidTokenAccountOwner
+    )]
+    pub voter_token_account: Account<'info, TokenAccount>,
+
+    #[account(mut, seeds = [b"proposal_vault", realm.key().as_ref(), proposal_id.to_be_bytes().as_ref()], bump)]
+    pub proposal_vault: Account<'info, TokenAccount>,
+
+    #[account(mut, seeds = [b"proposal", realm.key().as_ref(), proposal_id.to_be_bytes().as_ref()], bump)]
+    pub proposal_account: Account<'info, Proposal>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(proposal_id: u64)]
+pub struct SettleCommitment<'info> {
+    pub realm: Account<'info, Realm>,
+
+    #[account(mut, seeds = [b"proposal", realm.key().as_ref(), proposal_id.to_be_bytes().as_ref()], bump)]
+    pub proposal_account: Box<Account<'info, Proposal>>,
+
+    #[account(
+        mut,
+        seeds = [b"commitment", realm.key().as_ref(), proposal_id.to_be_bytes().as_ref(), voter.key().as_ref()],
+        bump,
+        close = voter
+    )]
+    pub commitment: Account<'info, VoteCommitment>,
+
+    /// CHECK: Committer; receives the commitment's rent.
+    #[account(mut)]
+    pub voter: AccountInfo<'info>,
+
+    #[account(
+        mut,
+        constraint = voter_token_account.mint == proposal_vault.mint @ VoteError::TokenMintMismatch,
+        constraint = voter_token_account.owner == voter.key() @ VoteError::InvalidTokenAccountOwner
+    )]
+    pub voter_token_account: Account<'info, TokenAccount>,
+
+    #[account(mut, seeds = [b"proposal_vault", realm.key().as_ref(), proposal_id.to_be_bytes().as_ref()], bump)]
+    pub proposal_vault: Account<'info, TokenAccount>,
+
+    /// CHECK: PDA that owns every proposal escrow vault.
+    #[account(seeds = [b"escrow_authority", realm.key().as_ref()], bump)]
+    pub escrow_authority: AccountInfo<'info>,
+
+    #[account(seeds = [b"treasury_config", realm.key().as_ref()], bump)]
+    pub treasury_config_account: Box<Account<'info, TreasuryConfig>>,
+
+    #[account(
+        mut,
+        constraint = treasury_token_account.key() == treasury_config_account.treasury_token_account @ VoteError::InvalidTokenAccountOwner
+    )]
+    pub treasury_token_account: Account<'info, TokenAccount>,
+
+    pub authority: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
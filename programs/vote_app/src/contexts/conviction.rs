@@ -0,0 +1,127 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::Mint;
+
+use crate::errors::VoteError;
+use crate::state::*;
+
+#[derive(Accounts)]
+pub struct CreateConvictionProposal<'info> {
+    pub realm: Account<'info, Realm>,
+
+    #[account(mut, seeds = [b"proposal_counter", realm.key().as_ref()], bump)]
+    pub proposal_counter_account: Account<'info, ProposalCounter>,
+
+    #[account(seeds = [b"governance_config", realm.key().as_ref()], bump = governance_config_account.bump)]
+    pub governance_config_account: Account<'info, GovernanceConfig>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + ConvictionProposal::INIT_SPACE,
+        seeds = [b"conviction_proposal", realm.key().as_ref(), proposal_counter_account.conviction_count.to_be_bytes().as_ref()],
+        bump
+    )]
+    pub conviction_proposal: Account<'info, ConvictionProposal>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(proposal_id: u64)]
+pub struct SupportConvictionProposal<'info> {
+    pub realm: Account<'info, Realm>,
+
+    #[account(
+        mut,
+        seeds = [b"conviction_proposal", realm.key().as_ref(), proposal_id.to_be_bytes().as_ref()],
+        bump
+    )]
+    pub conviction_proposal: Account<'info, ConvictionProposal>,
+
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + ConvictionSupport::INIT_SPACE,
+        seeds = [b"conviction_support", realm.key().as_ref(), proposal_id.to_be_bytes().as_ref(), authority.key().as_ref()],
+        bump
+    )]
+    pub conviction_support: Account<'info, ConvictionSupport>,
+
+    #[account(
+        mut,
+        seeds = [b"stake", realm.key().as_ref(), authority.key().as_ref()],
+        bump = stake_position.bump
+    )]
+    pub stake_position: Account<'info, StakePosition>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(proposal_id: u64)]
+pub struct WithdrawConvictionSupport<'info> {
+    pub realm: Account<'info, Realm>,
+
+    #[account(
+        mut,
+        seeds = [b"conviction_proposal", realm.key().as_ref(), proposal_id.to_be_bytes().as_ref()],
+        bump
+    )]
+    pub conviction_proposal: Account<'info, ConvictionProposal>,
+
+    #[account(
+        mut,
+        seeds = [b"conviction_support", realm.key().as_ref(), proposal_id.to_be_bytes().as_ref(), authority.key().as_ref()],
+        bump = conviction_support.bump
+    )]
+    pub conviction_support: Account<'info, ConvictionSupport>,
+
+    #[account(
+        mut,
+        seeds = [b"stake", realm.key().as_ref(), authority.key().as_ref()],
+        bump = stake_position.bump
+    )]
+    pub stake_position: Account<'info, StakePosition>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(proposal_id: u64)]
+pub struct ExecuteConvictionProposal<'info> {
+    pub realm: Account<'info, Realm>,
+
+    #[account(
+        mut,
+        seeds = [b"conviction_proposal", realm.key().as_ref(), proposal_id.to_be_bytes().as_ref()],
+        bump
+    )]
+    pub conviction_proposal: Account<'info, ConvictionProposal>,
+
+    #[account(seeds = [b"treasury_config", realm.key().as_ref()], bump)]
+    pub treasury_config: Account<'info, TreasuryConfig>,
+
+    /// CHECK: Treasury SOL vault PDA.
+    #[account(mut, seeds = [b"sol_vault", realm.key().as_ref()], bump = treasury_config.bump)]
+    pub sol_vault: AccountInfo<'info>,
+
+    #[account(seeds = [b"x_mint", realm.key().as_ref()], bump)]
+    pub x_mint: Account<'info, Mint>,
+
+    #[account(
+        mut,
+        constraint = beneficiary.key() == conviction_proposal.beneficiary @ VoteError::InvalidBeneficiary
+    )]
+    pub beneficiary: SystemAccount<'info>,
+
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
@@ -0,0 +1,69 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Mint, Token, TokenAccount};
+
+use crate::errors::VoteError;
+use crate::state::*;
+
+#[derive(Accounts)]
+pub struct DelegateVotes<'info> {
+    pub realm: Account<'info, Realm>,
+
+    #[account(seeds = [b"voter", realm.key().as_ref(), authority.key().as_ref()], bump)]
+    pub voter_account: Account<'info, Voter>,
+
+    /// CHECK: Wallet receiving the voting power; must be a registered voter.
+    #[account(constraint = delegate.key() != authority.key() @ VoteError::InvalidDelegate)]
+    pub delegate: AccountInfo<'info>,
+
+    #[account(seeds = [b"voter", realm.key().as_ref(), delegate.key().as_ref()], bump)]
+    pub delegate_voter_account: Account<'info, Voter>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + Delegation::INIT_SPACE,
+        seeds = [b"delegate", realm.key().as_ref(), authority.key().as_ref()],
+        bump
+    )]
+    pub delegation: Account<'info, Delegation>,
+
+    #[account(seeds = [b"x_mint", realm.key().as_ref()], bump)]
+    pub x_mint: Account<'info, Mint>,
+
+    #[account(
+        mut,
+        constraint = delegator_token_account.mint == x_mint.key() @ VoteError::TokenMintMismatch,
+        constraint = delegator_token_account.owner == authority.key() @ VoteError::InvalidTokenAccountOwner
+    )]
+    pub delegator_token_account: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UndelegateVotes<'info> {
+    pub realm: Account<'info, Realm>,
+
+    #[account(
+        mut,
+        seeds = [b"delegate", realm.key().as_ref(), authority.key().as_ref()],
+        bump = delegation.bump,
+        close = authority
+    )]
+    pub delegation: Account<'info, Delegation>,
+
+    #[account(
+        mut,
+        constraint = delegator_token_account.owner == authority.key() @ VoteError::InvalidTokenAccountOwner
+    )]
+    pub delegator_token_account: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
@@ -0,0 +1,84 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Token, TokenAccount};
+
+use crate::errors::VoteError;
+use crate::state::*;
+
+#[derive(Accounts)]
+#[instruction(proposal_id: u64)]
+pub struct WithdrawVoteTokens<'info> {
+    pub realm: Account<'info, Realm>,
+
+    #[account(mut, seeds = [b"proposal", realm.key().as_ref(), proposal_id.to_be_bytes().as_ref()], bump)]
+    pub proposal_account: Account<'info, Proposal>,
+
+    #[account(
+        mut,
+        seeds = [b"vote", realm.key().as_ref(), proposal_id.to_be_bytes().as_ref(), authority.key().as_ref()],
+        bump
+    )]
+    pub vote_record: Account<'info, VoteRecord>,
+
+    #[account(mut, seeds = [b"proposal_vault", realm.key().as_ref(), proposal_id.to_be_bytes().as_ref()], bump)]
+    pub proposal_vault: Account<'info, TokenAccount>,
+
+    /// CHECK: PDA that owns every proposal escrow vault.
+    #[account(seeds = [b"escrow_authority", realm.key().as_ref()], bump)]
+    pub escrow_authority: AccountInfo<'info>,
+
+    #[account(
+        mut,
+        constraint = voter_token_account.mint == proposal_vault.mint @ VoteError::TokenMintMismatch,
+        constraint = voter_token_account.owner == authority.key() @ VoteError::InvalidTokenAccountOwner
+    )]
+    pub voter_token_account: Account<'info, TokenAccount>,
+
+    /// The signer's delegation, re-approved for tokens its delegate escrowed.
+    #[account(seeds = [b"delegate", realm.key().as_ref(), authority.key().as_ref()], bump = delegation.bump)]
+    pub delegation: Option<Account<'info, Delegation>>,
+
+    pub authority: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+#[instruction(proposal_id: u64)]
+pub struct ReclaimProposalDeposit<'info> {
+    pub realm: Account<'info, Realm>,
+
+    #[account(
+        mut,
+        seeds = [b"proposal", realm.key().as_ref(), proposal_id.to_be_bytes().as_ref()],
+        bump,
+        constraint = proposal_account.authority == authority.key() @ VoteError::UnauthorizedAccess
+    )]
+    pub proposal_account: Account<'info, Proposal>,
+
+    #[account(mut, seeds = [b"proposal_vault", realm.key().as_ref(), proposal_id.to_be_bytes().as_ref()], bump)]
+    pub proposal_vault: Account<'info, TokenAccount>,
+
+    /// CHECK: PDA that owns every proposal escrow vault.
+    #[account(seeds = [b"escrow_authority", realm.key().as_ref()], bump)]
+    pub escrow_authority: AccountInfo<'info>,
+
+    #[account(
+        mut,
+        constraint = creator_token_account.mint == proposal_vault.mint @ VoteError::TokenMintMismatch,
+        constraint = creator_token_account.owner == authority.key() @ VoteError::InvalidTokenAccountOwner
+    )]
+    pub creator_token_account: Account<'info, TokenAccount>,
+
+    #[account(seeds = [b"treasury_config", realm.key().as_ref()], bump)]
+    pub treasury_config_account: Box<Account<'info, TreasuryConfig>>,
+
+    #[account(
+        mut,
+        constraint = treasury_token_account.key() == treasury_config_account.treasury_token_account @ VoteError::InvalidTokenAccountOwner
+    )]
+    pub treasury_token_account: Account<'info, TokenAccount>,
+
+    pub authority: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
@@ -6,6 +6,9 @@ use crate::state::*;
 
 #[derive(Accounts)]
 pub struct InitializeProposalCounter<'info> {
+    #[account(constraint = realm.authority == authority.key() @ VoteError::UnauthorizedAccess)]
+    pub realm: Account<'info, Realm>,
+
     #[account(mut)]
     pub authority: Signer<'info>,
 
@@ -13,7 +16,7 @@ pub struct InitializeProposalCounter<'info> {
         init,
         payer = authority,
         space = 8 + ProposalCounter::INIT_SPACE,
-        seeds = [b"proposal_counter"],
+        seeds = [b"proposal_counter", realm.key().as_ref()],
         bump
     )]
     pub proposal_counter_account: Box<Account<'info, ProposalCounter>>,
@@ -21,8 +24,51 @@ pub struct InitializeProposalCounter<'info> {
     pub system_program: Program<'info, System>,
 }
 
+#[derive(Accounts)]
+pub struct InitializeGovernanceConfig<'info> {
+    pub realm: Account<'info, Realm>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        seeds = [b"treasury_config", realm.key().as_ref()],
+        bump,
+        constraint = treasury_config_account.authority == authority.key() @ VoteError::UnauthorizedAccess
+    )]
+    pub treasury_config_account: Box<Account<'info, TreasuryConfig>>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + GovernanceConfig::INIT_SPACE,
+        seeds = [b"governance_config", realm.key().as_ref()],
+        bump
+    )]
+    pub governance_config_account: Box<Account<'info, GovernanceConfig>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateGovernanceConfig<'info> {
+    pub realm: Account<'info, Realm>,
+
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"governance_config", realm.key().as_ref()],
+        bump = governance_config_account.bump,
+        constraint = governance_config_account.authority == authority.key() @ VoteError::UnauthorizedAccess
+    )]
+    pub governance_config_account: Account<'info, GovernanceConfig>,
+}
+
 #[derive(Accounts)]
 pub struct RegisterVoter<'info> {
+    pub realm: Account<'info, Realm>,
+
     #[account(mut)]
     pub authority: Signer<'info>,
 
@@ -30,7 +76,7 @@ pub struct RegisterVoter<'info> {
         init,
         payer = authority,
         space = 8 + Voter::INIT_SPACE,
-        seeds = [b"voter", authority.key().as_ref()],
+        seeds = [b"voter", realm.key().as_ref(), authority.key().as_ref()],
         bump
     )]
     pub voter_account: Account<'info, Voter>,
@@ -40,6 +86,8 @@ pub struct RegisterVoter<'info> {
 
 #[derive(Accounts)]
 pub struct RegisterProposal<'info> {
+    pub realm: Account<'info, Realm>,
+
     #[account(mut)]
     pub authority: Signer<'info>,
 
@@ -47,14 +95,28 @@ pub struct RegisterProposal<'info> {
         init,
         payer = authority,
         space = 8 + Proposal::INIT_SPACE,
-        seeds = [b"proposal", proposal_counter_account.proposal_count.to_be_bytes().as_ref()],
+        seeds = [b"proposal", realm.key().as_ref(), proposal_counter_account.proposal_count.to_be_bytes().as_ref()],
         bump
     )]
     pub proposal_account: Account<'info, Proposal>,
 
-    #[account(mut)]
+    #[account(mut, seeds = [b"proposal_counter", realm.key().as_ref()], bump)]
     pub proposal_counter_account: Account<'info, ProposalCounter>,
 
+    #[account(
+        mut,
+        seeds = [b"voting_round", realm.key().as_ref(), voting_round.round_id.to_be_bytes().as_ref()],
+        bump
+    )]
+    pub voting_round: Option<Box<Account<'info, VotingRound>>>,
+
+    /// Must approve every proposal that joins `voting_round`.
+    pub round_authority: Option<Signer<'info>>,
+
+    #[account(seeds = [b"governance_config", realm.key().as_ref()], bump = governance_config_account.bump)]
+    pub governance_config_account: Account<'info, GovernanceConfig>,
+
+    #[account(seeds = [b"x_mint", realm.key().as_ref()], bump)]
     pub x_mint: Account<'info, Mint>,
 
     #[account(
@@ -64,24 +126,164 @@ pub struct RegisterProposal<'info> {
     )]
     pub proposal_token_account: Account<'info, TokenAccount>,
 
-    #[account(mut, constraint = treasury_token_account.mint == x_mint.key())]
-    pub treasury_token_account: Account<'info, TokenAccount>,
+    #[account(
+        init,
+        payer = authority,
+        token::mint = x_mint,
+        token::authority = escrow_authority,
+        seeds = [b"proposal_vault", realm.key().as_ref(), proposal_counter_account.proposal_count.to_be_bytes().as_ref()],
+        bump
+    )]
+    pub proposal_vault: Account<'info, TokenAccount>,
+
+    /// CHECK: PDA that owns every proposal escrow vault.
+    #[account(seeds = [b"escrow_authority", realm.key().as_ref()], bump)]
+    pub escrow_authority: AccountInfo<'info>,
 
     pub token_program: Program<'info, Token>,
     pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-#[instruction(proposal_id: u8)]
-pub struct Vote<'info> {
+#[instruction(legacy_proposal_id: u8)]
+pub struct MigrateLegacyProposal<'info> {
+    #[account(constraint = realm.authority == authority.key() @ VoteError::UnauthorizedAccess)]
+    pub realm: Account<'info, Realm>,
+
+    /// CHECK: Proposal stored under the pre-realm one-byte seed; decoded in the handler.
+    #[account(mut, seeds = [b"proposal", legacy_proposal_id.to_be_bytes().as_ref()], bump)]
+    pub legacy_proposal: AccountInfo<'info>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + Proposal::INIT_SPACE,
+        seeds = [b"proposal", realm.key().as_ref(), proposal_counter_account.proposal_count.to_be_bytes().as_ref()],
+        bump
+    )]
+    pub proposal_account: Box<Account<'info, Proposal>>,
+
+    #[account(mut, seeds = [b"proposal_counter", realm.key().as_ref()], bump)]
+    pub proposal_counter_account: Box<Account<'info, ProposalCounter>>,
+
+    #[account(seeds = [b"x_mint", realm.key().as_ref()], bump)]
+    pub x_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        init,
+        payer = authority,
+        token::mint = x_mint,
+        token::authority = escrow_authority,
+        seeds = [b"proposal_vault", realm.key().as_ref(), proposal_counter_account.proposal_count.to_be_bytes().as_ref()],
+        bump
+    )]
+    pub proposal_vault: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: PDA that owns every proposal escrow vault.
+    #[account(seeds = [b"escrow_authority", realm.key().as_ref()], bump)]
+    pub escrow_authority: AccountInfo<'info>,
+
+    /// Creator of the legacy proposal; must approve the migration and receives the
+    /// legacy account's rent once it is closed.
+    #[account(mut)]
+    pub legacy_authority: Signer<'info>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(proposal_id: u64)]
+pub struct AddProposalInstruction<'info> {
+    pub realm: Account<'info, Realm>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"proposal", realm.key().as_ref(), proposal_id.to_be_bytes().as_ref()],
+        bump,
+        constraint = proposal_account.authority == authority.key() @ VoteError::UnauthorizedAccess
+    )]
+    pub proposal_account: Account<'info, Proposal>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + ProposalInstruction::INIT_SPACE,
+        seeds = [
+            b"proposal_instruction",
+            realm.key().as_ref(),
+            proposal_id.to_be_bytes().as_ref(),
+            proposal_account.instruction_count.to_be_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub proposal_instruction: Account<'info, ProposalInstruction>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(proposal_id: u64)]
+pub struct ActivateProposal<'info> {
+    pub realm: Account<'info, Realm>,
+
+    #[account(
+        mut,
+        seeds = [b"proposal", realm.key().as_ref(), proposal_id.to_be_bytes().as_ref()],
+        bump,
+        constraint = proposal_account.authority == authority.key() @ VoteError::UnauthorizedAccess
+    )]
+    pub proposal_account: Account<'info, Proposal>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(proposal_id: u64)]
+pub struct CancelProposal<'info> {
+    pub realm: Account<'info, Realm>,
+
     #[account(
         mut,
-        seeds = [b"voter", authority.key().as_ref()],
+        seeds = [b"proposal", realm.key().as_ref(), proposal_id.to_be_bytes().as_ref()],
         bump,
-        constraint = voter_account.proposal_voted == 0 @ VoteError::VoterAlreadyVoted
+        constraint = proposal_account.authority == authority.key() @ VoteError::UnauthorizedAccess
     )]
+    pub proposal_account: Account<'info, Proposal>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(proposal_id: u64)]
+pub struct Vote<'info> {
+    pub realm: Account<'info, Realm>,
+
+    #[account(mut, seeds = [b"voter", realm.key().as_ref(), authority.key().as_ref()], bump)]
     pub voter_account: Account<'info, Voter>,
 
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + VoteRecord::INIT_SPACE,
+        seeds = [b"vote", realm.key().as_ref(), proposal_id.to_be_bytes().as_ref(), authority.key().as_ref()],
+        bump
+    )]
+    pub vote_record: Account<'info, VoteRecord>,
+
+    #[account(
+        seeds = [b"stake", realm.key().as_ref(), authority.key().as_ref()],
+        bump = stake_position.bump
+    )]
+    pub stake_position: Option<Account<'info, StakePosition>>,
+
+    #[account(seeds = [b"x_mint", realm.key().as_ref()], bump)]
     pub x_mint: Account<'info, Mint>,
 
     #[account(
@@ -91,63 +293,198 @@ pub struct Vote<'info> {
     )]
     pub voter_token_account: Account<'info, TokenAccount>,
 
-    #[account(mut, constraint = treasury_token_account.mint == x_mint.key())]
-    pub treasury_token_account: Account<'info, TokenAccount>,
+    #[account(mut, seeds = [b"proposal_vault", realm.key().as_ref(), proposal_id.to_be_bytes().as_ref()], bump)]
+    pub proposal_vault: Account<'info, TokenAccount>,
 
-    #[account(mut, seeds = [b"proposal", proposal_id.to_be_bytes().as_ref()], bump)]
+    #[account(mut, seeds = [b"proposal", realm.key().as_ref(), proposal_id.to_be_bytes().as_ref()], bump)]
     pub proposal_account: Account<'info, Proposal>,
 
     #[account(mut)]
     pub authority: Signer<'info>,
 
     pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-#[instruction(proposal_id: u8)]
+#[instruction(proposal_id: u64)]
+pub struct ChangeVote<'info> {
+    pub realm: Account<'info, Realm>,
+
+    #[account(mut, seeds = [b"voter", realm.key().as_ref(), authority.key().as_ref()], bump)]
+    pub voter_account: Account<'info, Voter>,
+
+    #[account(
+        mut,
+        seeds = [b"vote", realm.key().as_ref(), proposal_id.to_be_bytes().as_ref(), authority.key().as_ref()],
+        bump,
+        constraint = vote_record.voter == authority.key() @ VoteError::InvalidVoteRecord
+    )]
+    pub vote_record: Account<'info, VoteRecord>,
+
+    #[account(mut, seeds = [b"proposal", realm.key().as_ref(), proposal_id.to_be_bytes().as_ref()], bump)]
+    pub proposal_account: Account<'info, Proposal>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(proposal_id: u64)]
+pub struct RetractVote<'info> {
+    pub realm: Account<'info, Realm>,
+
+    #[account(mut, seeds = [b"voter", realm.key().as_ref(), authority.key().as_ref()], bump)]
+    pub voter_account: Account<'info, Voter>,
+
+    #[account(
+        mut,
+        seeds = [b"vote", realm.key().as_ref(), proposal_id.to_be_bytes().as_ref(), authority.key().as_ref()],
+        bump,
+        constraint = vote_record.voter == authority.key() @ VoteError::InvalidVoteRecord,
+        close = rent_payer
+    )]
+    pub vote_record: Account<'info, VoteRecord>,
+
+    /// CHECK: Wallet that paid the vote record's rent; receives it back.
+    #[account(mut, address = vote_record.rent_payer @ VoteError::InvalidVoteRecord)]
+    pub rent_payer: AccountInfo<'info>,
+
+    #[account(mut, seeds = [b"proposal", realm.key().as_ref(), proposal_id.to_be_bytes().as_ref()], bump)]
+    pub proposal_account: Account<'info, Proposal>,
+
+    #[account(mut, seeds = [b"proposal_vault", realm.key().as_ref(), proposal_id.to_be_bytes().as_ref()], bump)]
+    pub proposal_vault: Account<'info, TokenAccount>,
+
+    /// CHECK: PDA that owns every proposal escrow vault.
+    #[account(seeds = [b"escrow_authority", realm.key().as_ref()], bump)]
+    pub escrow_authority: AccountInfo<'info>,
+
+    #[account(
+        mut,
+        constraint = voter_token_account.mint == proposal_vault.mint @ VoteError::TokenMintMismatch,
+        constraint = voter_token_account.owner == authority.key() @ VoteError::InvalidTokenAccountOwner
+    )]
+    pub voter_token_account: Account<'info, TokenAccount>,
+
+    /// The signer's delegation, re-approved for tokens its delegate escrowed.
+    #[account(seeds = [b"delegate", realm.key().as_ref(), authority.key().as_ref()], bump = delegation.bump)]
+    pub delegation: Option<Account<'info, Delegation>>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+#[instruction(proposal_id: u64)]
 pub struct PickWinner<'info> {
+    pub realm: Account<'info, Realm>,
+
+    #[account(mut, seeds = [b"proposal", realm.key().as_ref(), proposal_id.to_be_bytes().as_ref()], bump)]
+    pub proposal_account: Account<'info, Proposal>,
+
     #[account(
         init_if_needed,
         payer = authority,
-        space = 8 + Winner::INIT_SPACE,
-        seeds = [b"winner"],
+        space = 8 + ProposalResult::INIT_SPACE,
+        seeds = [b"proposal_result", realm.key().as_ref(), proposal_id.to_be_bytes().as_ref()],
         bump
     )]
-    pub winner_account: Account<'info, Winner>,
+    pub proposal_result: Box<Account<'info, ProposalResult>>,
 
-    #[account(seeds = [b"proposal", proposal_id.to_be_bytes().as_ref()], bump)]
-    pub proposal_account: Account<'info, Proposal>,
+    #[account(
+        seeds = [b"voting_round", realm.key().as_ref(), voting_round.round_id.to_be_bytes().as_ref()],
+        bump
+    )]
+    pub voting_round: Option<Box<Account<'info, VotingRound>>>,
+
+    #[account(
+        seeds = [b"voting_round", realm.key().as_ref(), runoff_round.round_id.to_be_bytes().as_ref()],
+        bump
+    )]
+    pub runoff_round: Option<Box<Account<'info, VotingRound>>>,
 
     #[account(mut)]
     pub authority: Signer<'info>,
+
     pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-#[instruction(proposal_id: u8)]
+#[instruction(proposal_id: u64)]
+pub struct ExecuteProposal<'info> {
+    pub realm: Account<'info, Realm>,
+
+    #[account(mut, seeds = [b"proposal", realm.key().as_ref(), proposal_id.to_be_bytes().as_ref()], bump)]
+    pub proposal_account: Account<'info, Proposal>,
+
+    #[account(
+        mut,
+        seeds = [
+            b"proposal_instruction",
+            realm.key().as_ref(),
+            proposal_id.to_be_bytes().as_ref(),
+            proposal_account.instructions_executed.to_be_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub proposal_instruction: Option<Account<'info, ProposalInstruction>>,
+
+    /// CHECK: PDA that signs executed proposal instructions on behalf of the DAO.
+    #[account(seeds = [b"governance", realm.key().as_ref()], bump)]
+    pub governance_authority: AccountInfo<'info>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(proposal_id: u64)]
 pub struct CloseProposal<'info> {
+    pub realm: Account<'info, Realm>,
+
     #[account(
         mut,
-        seeds = [b"proposal", proposal_id.to_be_bytes().as_ref()],
+        seeds = [b"proposal", realm.key().as_ref(), proposal_id.to_be_bytes().as_ref()],
         bump,
         close = destination,
         constraint = proposal_account.authority == authority.key() @ VoteError::UnauthorizedAccess
     )]
     pub proposal_account: Account<'info, Proposal>,
 
-   
+    #[account(mut, seeds = [b"proposal_vault", realm.key().as_ref(), proposal_id.to_be_bytes().as_ref()], bump)]
+    pub proposal_vault: Account<'info, TokenAccount>,
+
+    /// CHECK: PDA that owns every proposal escrow vault.
+    #[account(seeds = [b"escrow_authority", realm.key().as_ref()], bump)]
+    pub escrow_authority: AccountInfo<'info>,
+
+    #[account(seeds = [b"treasury_config", realm.key().as_ref()], bump)]
+    pub treasury_config_account: Box<Account<'info, TreasuryConfig>>,
+
+    #[account(
+        mut,
+        constraint = treasury_token_account.key() == treasury_config_account.treasury_token_account @ VoteError::InvalidTokenAccountOwner
+    )]
+    pub treasury_token_account: Account<'info, TokenAccount>,
+
+    /// CHECK: Only receives the lamports of the closed proposal account.
     #[account(mut)]
     pub destination: AccountInfo<'info>,
 
     #[account(mut)]
     pub authority: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
 }
 
 #[derive(Accounts)]
 pub struct CloseVoter<'info> {
+    pub realm: Account<'info, Realm>,
+
     #[account(
         mut,
-        seeds = [b"voter", authority.key().as_ref()],
+        seeds = [b"voter", realm.key().as_ref(), authority.key().as_ref()],
         bump,
         close = authority
     )]
@@ -1,5 +1,21 @@
+pub mod commit_reveal;
+pub mod conviction;
+pub mod delegation;
+pub mod escrow;
 pub mod governance;
+pub mod ranked_choice;
+pub mod realm;
+pub mod round;
+pub mod stake;
 pub mod treasury;
 
+pub use commit_reveal::*;
+pub use conviction::*;
+pub use delegation::*;
+pub use escrow::*;
 pub use governance::*;
+pub use ranked_choice::*;
+pub use realm::*;
+pub use round::*;
+pub use stake::*;
 pub use treasury::*;
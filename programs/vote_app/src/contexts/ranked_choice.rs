@@ -0,0 +1,85 @@
+use anchor_lang::prelude::*;
+
+use crate::state::*;
+
+#[derive(Accounts)]
+#[instruction(round_id: u64)]
+pub struct SubmitRankedBallot<'info> {
+    pub realm: Account<'info, Realm>,
+
+    #[account(
+        mut,
+        seeds = [b"voting_round", realm.key().as_ref(), round_id.to_be_bytes().as_ref()],
+        bump
+    )]
+    pub voting_round: Account<'info, VotingRound>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + RankedBallot::INIT_SPACE,
+        seeds = [b"ballot", realm.key().as_ref(), round_id.to_be_bytes().as_ref(), authority.key().as_ref()],
+        bump
+    )]
+    pub ballot: Account<'info, RankedBallot>,
+
+    #[account(
+        seeds = [b"stake", realm.key().as_ref(), authority.key().as_ref()],
+        bump = stake_position.bump
+    )]
+    pub stake_position: Account<'info, StakePosition>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(round_id: u64)]
+pub struct StartRankedTally<'info> {
+    pub realm: Account<'info, Realm>,
+
+    #[account(
+        mut,
+        seeds = [b"voting_round", realm.key().as_ref(), round_id.to_be_bytes().as_ref()],
+        bump
+    )]
+    pub voting_round: Account<'info, VotingRound>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + RankedTally::INIT_SPACE,
+        seeds = [b"ranked_tally", realm.key().as_ref(), round_id.to_be_bytes().as_ref()],
+        bump
+    )]
+    pub ranked_tally: Account<'info, RankedTally>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(round_id: u64)]
+pub struct TallyRankedBallots<'info> {
+    pub realm: Account<'info, Realm>,
+
+    #[account(
+        mut,
+        seeds = [b"voting_round", realm.key().as_ref(), round_id.to_be_bytes().as_ref()],
+        bump
+    )]
+    pub voting_round: Account<'info, VotingRound>,
+
+    #[account(
+        mut,
+        seeds = [b"ranked_tally", realm.key().as_ref(), round_id.to_be_bytes().as_ref()],
+        bump = ranked_tally.bump
+    )]
+    pub ranked_tally: Account<'info, RankedTally>,
+
+    pub authority: Signer<'info>,
+}
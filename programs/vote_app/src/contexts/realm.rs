@@ -0,0 +1,21 @@
+use anchor_lang::prelude::*;
+
+use crate::state::*;
+
+#[derive(Accounts)]
+#[instruction(name: String)]
+pub struct CreateRealm<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + Realm::INIT_SPACE,
+        seeds = [b"realm", name.as_bytes()],
+        bump
+    )]
+    pub realm: Account<'info, Realm>,
+
+    pub system_program: Program<'info, System>,
+}
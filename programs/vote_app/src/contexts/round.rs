@@ -0,0 +1,79 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::VoteError;
+use crate::state::*;
+
+#[derive(Accounts)]
+pub struct CreateVotingRound<'info> {
+    #[account(constraint = realm.authority == authority.key() @ VoteError::UnauthorizedAccess)]
+    pub realm: Account<'info, Realm>,
+
+    #[account(mut, seeds = [b"proposal_counter", realm.key().as_ref()], bump)]
+    pub proposal_counter_account: Account<'info, ProposalCounter>,
+
+    #[account(
+        seeds = [b"governance_config", realm.key().as_ref()],
+        bump = governance_config_account.bump
+    )]
+    pub governance_config_account: Account<'info, GovernanceConfig>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + VotingRound::INIT_SPACE,
+        seeds = [b"voting_round", realm.key().as_ref(), proposal_counter_account.round_count.to_be_bytes().as_ref()],
+        bump
+    )]
+    pub voting_round: Account<'info, VotingRound>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(round_id: u64)]
+pub struct CreateRunoffRound<'info> {
+    #[account(constraint = realm.authority == authority.key() @ VoteError::UnauthorizedAccess)]
+    pub realm: Account<'info, Realm>,
+
+    #[account(mut, seeds = [b"proposal_counter", realm.key().as_ref()], bump)]
+    pub proposal_counter_account: Account<'info, ProposalCounter>,
+
+    #[account(
+        mut,
+        seeds = [b"voting_round", realm.key().as_ref(), round_id.to_be_bytes().as_ref()],
+        bump
+    )]
+    pub tied_round: Account<'info, VotingRound>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + VotingRound::INIT_SPACE,
+        seeds = [b"voting_round", realm.key().as_ref(), proposal_counter_account.round_count.to_be_bytes().as_ref()],
+        bump
+    )]
+    pub runoff_round: Account<'info, VotingRound>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(round_id: u64)]
+pub struct FinalizeRound<'info> {
+    pub realm: Account<'info, Realm>,
+
+    #[account(
+        mut,
+        seeds = [b"voting_round", realm.key().as_ref(), round_id.to_be_bytes().as_ref()],
+        bump
+    )]
+    pub voting_round: Account<'info, VotingRound>,
+
+    pub authority: Signer<'info>,
+}
@@ -0,0 +1,81 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Mint, Token, TokenAccount};
+
+use crate::errors::VoteError;
+use crate::state::*;
+
+#[derive(Accounts)]
+pub struct StakeTokens<'info> {
+    pub realm: Account<'info, Realm>,
+
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + StakePosition::INIT_SPACE,
+        seeds = [b"stake", realm.key().as_ref(), authority.key().as_ref()],
+        bump
+    )]
+    pub stake_position: Account<'info, StakePosition>,
+
+    #[account(seeds = [b"x_mint", realm.key().as_ref()], bump)]
+    pub x_mint: Account<'info, Mint>,
+
+    #[account(
+        init_if_needed,
+        payer = authority,
+        token::mint = x_mint,
+        token::authority = escrow_authority,
+        seeds = [b"stake_vault", realm.key().as_ref()],
+        bump
+    )]
+    pub stake_vault: Account<'info, TokenAccount>,
+
+    /// CHECK: PDA that owns every escrow vault, including the stake vault.
+    #[account(seeds = [b"escrow_authority", realm.key().as_ref()], bump)]
+    pub escrow_authority: AccountInfo<'info>,
+
+    #[account(
+        mut,
+        constraint = staker_token_account.mint == x_mint.key() @ VoteError::TokenMintMismatch,
+        constraint = staker_token_account.owner == authority.key() @ VoteError::InvalidTokenAccountOwner
+    )]
+    pub staker_token_account: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct Unstake<'info> {
+    pub realm: Account<'info, Realm>,
+
+    #[account(
+        mut,
+        seeds = [b"stake", realm.key().as_ref(), authority.key().as_ref()],
+        bump = stake_position.bump,
+        close = authority
+    )]
+    pub stake_position: Account<'info, StakePosition>,
+
+    #[account(mut, seeds = [b"stake_vault", realm.key().as_ref()], bump)]
+    pub stake_vault: Account<'info, TokenAccount>,
+
+    /// CHECK: PDA that owns every escrow vault, including the stake vault.
+    #[account(seeds = [b"escrow_authority", realm.key().as_ref()], bump)]
+    pub escrow_authority: AccountInfo<'info>,
+
+    #[account(
+        mut,
+        constraint = staker_token_account.mint == stake_vault.mint @ VoteError::TokenMintMismatch,
+        constraint = staker_token_account.owner == authority.key() @ VoteError::InvalidTokenAccountOwner
+    )]
+    pub staker_token_account: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
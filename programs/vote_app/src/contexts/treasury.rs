@@ -6,6 +6,9 @@ use crate::state::*;
 
 #[derive(Accounts)]
 pub struct InitializeTreasury<'info> {
+    #[account(constraint = realm.authority == authority.key() @ VoteError::UnauthorizedAccess)]
+    pub realm: Account<'info, Realm>,
+
     #[account(mut)]
     pub authority: Signer<'info>,
 
@@ -13,7 +16,7 @@ pub struct InitializeTreasury<'info> {
         init,
         payer = authority,
         space = 8 + TreasuryConfig::INIT_SPACE,
-        seeds = [b"treasury_config"],
+        seeds = [b"treasury_config", realm.key().as_ref()],
         bump
     )]
     pub treasury_config_account: Box<Account<'info, TreasuryConfig>>,
@@ -23,17 +26,17 @@ pub struct InitializeTreasury<'info> {
         payer = authority,
         mint::authority = mint_authority,
         mint::decimals = 6,
-        seeds = [b"x_mint"],
+        seeds = [b"x_mint", realm.key().as_ref()],
         bump
     )]
     pub x_mint: Box<Account<'info, Mint>>,
 
     /// CHECK: This PDA receives SOL from token purchases.
-    #[account(mut, seeds = [b"sol_vault"], bump)]
+    #[account(mut, seeds = [b"sol_vault", realm.key().as_ref()], bump)]
     pub sol_vault: AccountInfo<'info>,
 
     /// CHECK: PDA used as mint authority for `x_mint`.
-    #[account(seeds = [b"mint_authority"], bump)]
+    #[account(seeds = [b"mint_authority", realm.key().as_ref()], bump)]
     pub mint_authority: AccountInfo<'info>,
 
     pub token_program: Program<'info, Token>,
@@ -42,31 +45,46 @@ pub struct InitializeTreasury<'info> {
 
 #[derive(Accounts)]
 pub struct ConfigureTreasuryTokenAccount<'info> {
+    pub realm: Account<'info, Realm>,
+
     #[account(mut)]
     pub authority: Signer<'info>,
 
     #[account(
         mut,
-        seeds = [b"treasury_config"],
+        seeds = [b"treasury_config", realm.key().as_ref()],
         bump,
         constraint = treasury_config_account.authority == authority.key() @ VoteError::UnauthorizedAccess
     )]
     pub treasury_config_account: Account<'info, TreasuryConfig>,
 
+    #[account(seeds = [b"x_mint", realm.key().as_ref()], bump)]
     pub x_mint: Account<'info, Mint>,
 
     #[account(
-        mut,
-        constraint = treasury_token_account.mint == x_mint.key() @ VoteError::InvalidMint,
-        constraint = treasury_token_account.owner == authority.key() @ VoteError::InvalidTokenAccountOwner
+        init,
+        payer = authority,
+        token::mint = x_mint,
+        token::authority = governance_authority,
+        seeds = [b"treasury_tokens", realm.key().as_ref()],
+        bump
     )]
     pub treasury_token_account: Account<'info, TokenAccount>,
+
+    /// CHECK: Governance PDA that owns the treasury token account.
+    #[account(seeds = [b"governance", realm.key().as_ref()], bump)]
+    pub governance_authority: AccountInfo<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
 pub struct BuyTokens<'info> {
+    pub realm: Account<'info, Realm>,
+
     #[account(
-        seeds = [b"treasury_config"],
+        seeds = [b"treasury_config", realm.key().as_ref()],
         bump,
         constraint = treasury_config_account.x_mint == x_mint.key() @ VoteError::InvalidMint,
         constraint = treasury_config_account.treasury_token_account == treasury_token_account.key() @ VoteError::InvalidTokenAccountOwner
@@ -74,7 +92,7 @@ pub struct BuyTokens<'info> {
     pub treasury_config_account: Account<'info, TreasuryConfig>,
 
     /// CHECK: Treasury SOL vault PDA.
-    #[account(mut, seeds = [b"sol_vault"], bump = treasury_config_account.bump)]
+    #[account(mut, seeds = [b"sol_vault", realm.key().as_ref()], bump = treasury_config_account.bump)]
     pub sol_vault: AccountInfo<'info>,
 
     #[account(
@@ -94,7 +112,7 @@ pub struct BuyTokens<'info> {
     pub buyer_token_account: Account<'info, TokenAccount>,
 
     /// CHECK: PDA mint authority.
-    #[account(seeds = [b"mint_authority"], bump)]
+    #[account(seeds = [b"mint_authority", realm.key().as_ref()], bump)]
     pub mint_authority: AccountInfo<'info>,
 
     #[account(mut)]
@@ -106,18 +124,43 @@ pub struct BuyTokens<'info> {
 
 #[derive(Accounts)]
 pub struct WithdrawSol<'info> {
-    #[account(
-        seeds = [b"treasury_config"],
-        bump,
-        constraint = treasury_config.authority == authority.key() @ VoteError::UnauthorizedAccess
-    )]
+    pub realm: Account<'info, Realm>,
+
+    #[account(seeds = [b"treasury_config", realm.key().as_ref()], bump)]
     pub treasury_config: Account<'info, TreasuryConfig>,
 
     /// CHECK: Treasury SOL vault PDA.
-    #[account(mut, seeds = [b"sol_vault"], bump = treasury_config.bump)]
+    #[account(mut, seeds = [b"sol_vault", realm.key().as_ref()], bump = treasury_config.bump)]
     pub sol_vault: AccountInfo<'info>,
 
+    #[account(seeds = [b"governance", realm.key().as_ref()], bump)]
+    pub governance_authority: Signer<'info>,
+
     #[account(mut)]
-    pub authority: Signer<'info>,
+    pub recipient: SystemAccount<'info>,
+
     pub system_program: Program<'info, System>,
 }
+
+#[derive(Accounts)]
+pub struct WithdrawTreasuryTokens<'info> {
+    pub realm: Account<'info, Realm>,
+
+    #[account(
+        mut,
+        seeds = [b"treasury_tokens", realm.key().as_ref()],
+        bump
+    )]
+    pub treasury_token_account: Account<'info, TokenAccount>,
+
+    #[account(seeds = [b"governance", realm.key().as_ref()], bump)]
+    pub governance_authority: Signer<'info>,
+
+    #[account(
+        mut,
+        constraint = recipient_token_account.mint == treasury_token_account.mint @ VoteError::InvalidMint
+    )]
+    pub recipient_token_account: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+}
@@ -37,4 +37,187 @@ pub enum VoteError {
 
     #[msg("Provided mint account is invalid")]
     InvalidMint,
+
+    #[msg("Vote weight must be greater than zero")]
+    InvalidVoteWeight,
+
+    #[msg("Vote weight overflow - maximum weight exceeded")]
+    ProposalWeightOverflow,
+
+    #[msg("Remaining accounts do not match the expected layout")]
+    InvalidRemainingAccounts,
+
+    #[msg("Vote record does not belong to the expected voter or proposal")]
+    InvalidVoteRecord,
+
+    #[msg("Proposal must have between 2 and 8 options")]
+    InvalidOptionCount,
+
+    #[msg("Option labels must be non-empty and at most 32 bytes")]
+    InvalidOptionLabel,
+
+    #[msg("Selected option does not exist on this proposal")]
+    InvalidOptionIndex,
+
+    #[msg("Proposal result has already been declared")]
+    ProposalAlreadyResolved,
+
+    #[msg("Governance config contains an out-of-range or inconsistent value")]
+    InvalidGovernanceConfig,
+
+    #[msg("Proposal cannot move from its current state to the requested state")]
+    InvalidStateTransition,
+
+    #[msg("Proposal is not open for voting")]
+    ProposalNotActive,
+
+    #[msg("Proposal instructions can only be changed while the proposal is a draft")]
+    ProposalNotDraft,
+
+    #[msg("Proposal already holds the maximum number of instructions")]
+    TooManyProposalInstructions,
+
+    #[msg("Proposal instruction exceeds the allowed number of accounts or data length")]
+    InvalidProposalInstruction,
+
+    #[msg("The next proposal instruction account was not provided")]
+    MissingProposalInstruction,
+
+    #[msg("Escrowed tokens can only be released once the proposal is resolved or cancelled")]
+    ProposalNotSettled,
+
+    #[msg("There are no escrowed tokens to release")]
+    NothingToWithdraw,
+
+    #[msg("Proposal escrow still holds tokens owed to its creator or voters")]
+    EscrowNotEmpty,
+
+    #[msg("Vote record still holds locked tokens; withdraw them first")]
+    VoteTokensStillLocked,
+
+    #[msg("Realm name must be between 1 and 32 bytes")]
+    InvalidRealmName,
+
+    #[msg("Account is not a proposal created under the legacy one-byte seed")]
+    InvalidLegacyProposal,
+
+    #[msg("Voting round must end after it starts and in the future")]
+    InvalidVotingRound,
+
+    #[msg("Voting round is not open for new proposals")]
+    VotingRoundClosed,
+
+    #[msg("Voting round already holds the maximum number of proposals")]
+    VotingRoundFull,
+
+    #[msg("Voting round has not ended yet")]
+    VotingRoundStillOpen,
+
+    #[msg("Voting round has already been finalized")]
+    VotingRoundAlreadyFinalized,
+
+    #[msg("Proposal does not belong to this voting round")]
+    ProposalNotInRound,
+
+    #[msg("Every proposal of the voting round must be supplied")]
+    IncompleteRoundProposals,
+
+    #[msg("Proposal belongs to a voting round that has not been finalized")]
+    RoundNotFinalized,
+
+    #[msg("Votes cannot be delegated to this account")]
+    InvalidDelegate,
+
+    #[msg("Delegation does not belong to the voting delegate")]
+    InvalidDelegation,
+
+    #[msg("Vote already has this choice")]
+    VoteUnchanged,
+
+    #[msg("Lock duration must be positive and at most the maximum lock")]
+    InvalidLockDuration,
+
+    #[msg("Staked tokens are still locked")]
+    StakeStillLocked,
+
+    #[msg("Stake lock ends before the proposal deadline")]
+    StakeExpiresBeforeDeadline,
+
+    #[msg("Stake position held no tokens at the proposal snapshot")]
+    NoStakeAtSnapshot,
+
+    #[msg("Proposal only counts staked voting power from its snapshot")]
+    SnapshotVotingRequiresStake,
+
+    #[msg("Voting round does not use ranked-choice ballots")]
+    NotRankedChoiceRound,
+
+    #[msg("Ranked-choice rounds are resolved by the instant-runoff tally")]
+    RankedChoiceRound,
+
+    #[msg("Rankings must list distinct proposals of the voting round")]
+    InvalidRanking,
+
+    #[msg("Ballot does not belong to this voting round")]
+    InvalidBallot,
+
+    #[msg("Conviction voting is not enabled for this DAO")]
+    ConvictionVotingDisabled,
+
+    #[msg("Not enough unallocated stake")]
+    InsufficientStake,
+
+    #[msg("Staked tokens still support conviction proposals")]
+    StakeAllocatedToConviction,
+
+    #[msg("Conviction proposal has already been executed")]
+    ConvictionProposalAlreadyExecuted,
+
+    #[msg("Requested amount exceeds the share of the treasury a proposal may request")]
+    RequestExceedsMaxRatio,
+
+    #[msg("Conviction has not reached the proposal's threshold")]
+    ConvictionThresholdNotReached,
+
+    #[msg("Beneficiary does not match the proposal")]
+    InvalidBeneficiary,
+
+    #[msg("Amount must be greater than zero")]
+    InvalidAmount,
+
+    #[msg("Proposal only accepts committed votes")]
+    CommitRevealRequired,
+
+    #[msg("Proposal does not use commit-reveal voting")]
+    NotCommitRevealProposal,
+
+    #[msg("Reveal window is not open")]
+    RevealWindowNotOpen,
+
+    #[msg("Revealed vote does not match the commitment")]
+    CommitmentMismatch,
+
+    #[msg("Commitment has already been revealed")]
+    CommitmentAlreadyRevealed,
+
+    #[msg("Voting on this proposal has not started yet")]
+    VotingNotStarted,
+
+    #[msg("Voting period is outside the DAO's allowed range")]
+    InvalidVotingPeriod,
+
+    #[msg("Delegated votes must follow the delegate's own choice")]
+    DelegateChoiceMismatch,
+
+    #[msg("Minimum voting period must be positive and not above the maximum")]
+    InvalidVotingPeriodBounds,
+
+    #[msg("Only proposals won by their approving option can be executed")]
+    ProposalNotApproved,
+
+    #[msg("Proposal tied for its round and awaits the runoff round")]
+    RunoffPending,
+
+    #[msg("Round has no tie awaiting a runoff round")]
+    NoRunoffRequired,
 }
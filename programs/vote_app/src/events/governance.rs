@@ -1,11 +1,28 @@
 use anchor_lang::prelude::*;
 
+use crate::state::{DefeatReason, GovernanceParams, ProposalState, TiePolicy};
+
+#[event]
+pub struct RealmCreated {
+    pub realm: Pubkey,
+    pub authority: Pubkey,
+    pub name: String,
+    pub timestamp: i64,
+}
+
 #[event]
 pub struct ProposalCounterInitialized {
     pub authority: Pubkey,
     pub timestamp: i64,
 }
 
+#[event]
+pub struct GovernanceConfigUpdated {
+    pub authority: Pubkey,
+    pub params: GovernanceParams,
+    pub timestamp: i64,
+}
+
 #[event]
 pub struct VoterRegistered {
     pub voter: Pubkey,
@@ -15,35 +32,310 @@ pub struct VoterRegistered {
 
 #[event]
 pub struct ProposalCreated {
-    pub proposal_id: u8,
+    pub proposal_id: u64,
     pub creator: Pubkey,
     pub proposal_info: String,
+    pub options: Vec<String>,
+    pub voting_starts_at: i64,
+    pub deadline: i64,
+    pub round_id: Option<u64>,
+    pub snapshot_at: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct LegacyProposalMigrated {
+    pub legacy_proposal_id: u8,
+    pub proposal_id: u64,
+    pub legacy_proposal: Pubkey,
+    pub migrated_by: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct VotingRoundCreated {
+    pub round_id: u64,
+    pub authority: Pubkey,
+    pub starts_at: i64,
+    pub ends_at: i64,
+    pub ranked_choice: bool,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct RunoffRoundCreated {
+    pub round_id: u64,
+    pub runoff_of: u64,
+    pub proposal_ids: Vec<u64>,
+    pub starts_at: i64,
+    pub ends_at: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct RankedBallotSubmitted {
+    pub round_id: u64,
+    pub voter: Pubkey,
+    pub rankings: Vec<u64>,
+    pub weight: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct CandidateEliminated {
+    pub round_id: u64,
+    pub pass: u32,
+    pub proposal_id: u64,
+    pub weight: u64,
+    pub exhausted_weight: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct RoundFinalized {
+    pub round_id: u64,
+    pub winning_proposal_id: Option<u64>,
+    pub winning_weight: u64,
+    pub finalized_by: Pubkey,
+    pub timestamp: i64,
+}
+
+/// Emitted when the leading entries are tied. `tied` holds option indices when a
+/// proposal is resolved and proposal ids when a round is finalized.
+#[event]
+pub struct TieDetected {
+    pub proposal_id: Option<u64>,
+    pub round_id: Option<u64>,
+    pub tied: Vec<u64>,
+    pub weight: u64,
+    pub policy: TiePolicy,
+    pub winner: Option<u64>,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ProposalActivated {
+    pub proposal_id: u64,
+    pub activated_by: Pubkey,
     pub deadline: i64,
     pub timestamp: i64,
 }
 
+#[event]
+pub struct ProposalCancelled {
+    pub proposal_id: u64,
+    pub previous_state: ProposalState,
+    pub cancelled_by: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ProposalInstructionAdded {
+    pub proposal_id: u64,
+    pub index: u8,
+    pub program_id: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ProposalInstructionExecuted {
+    pub proposal_id: u64,
+    pub index: u8,
+    pub program_id: Pubkey,
+    pub executed_by: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ProposalExecuted {
+    pub proposal_id: u64,
+    pub executed_by: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct VoteCommitted {
+    pub voter: Pubkey,
+    pub proposal_id: u64,
+    pub bond: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct CommitmentSettled {
+    pub voter: Pubkey,
+    pub proposal_id: u64,
+    pub revealed: bool,
+    pub bond: u64,
+    pub slashed: bool,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct UnrevealedCommitments {
+    pub proposal_id: u64,
+    pub count: u64,
+    pub bond_per_commitment: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct DeadlineExtended {
+    pub proposal_id: u64,
+    pub triggered_by: Pubkey,
+    pub previous_deadline: i64,
+    pub new_deadline: i64,
+    pub extensions: u8,
+    pub timestamp: i64,
+}
+
 #[event]
 pub struct VoteCast {
     pub voter: Pubkey,
-    pub proposal_id: u8,
+    pub cast_by: Pubkey,
+    pub proposal_id: u64,
+    pub choice: u8,
+    pub tokens: u64,
+    pub weight: u64,
+    pub option_weight: u64,
     pub total_votes: u64,
+    pub total_weight: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct VoteChanged {
+    pub voter: Pubkey,
+    pub proposal_id: u64,
+    pub previous_choice: u8,
+    pub new_choice: u8,
+    pub weight: u64,
+    pub previous_option_weight: u64,
+    pub new_option_weight: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct VoteRetracted {
+    pub voter: Pubkey,
+    pub proposal_id: u64,
+    pub choice: u8,
+    pub weight: u64,
+    pub tokens_refunded: u64,
+    pub total_votes: u64,
+    pub total_weight: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct TokensStaked {
+    pub owner: Pubkey,
+    pub amount: u64,
+    pub total_staked: u64,
+    pub lock_end: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct TokensUnstaked {
+    pub owner: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ConvictionProposalCreated {
+    pub proposal_id: u64,
+    pub creator: Pubkey,
+    pub beneficiary: Pubkey,
+    pub requested_lamports: u64,
+    pub half_life: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ConvictionSupportUpdated {
+    pub proposal_id: u64,
+    pub supporter: Pubkey,
+    pub supporter_amount: u64,
+    pub staked_tokens: u64,
+    pub conviction: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ConvictionProposalExecuted {
+    pub proposal_id: u64,
+    pub beneficiary: Pubkey,
+    pub amount: u64,
+    pub conviction: u64,
+    pub threshold: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct VotesDelegated {
+    pub delegator: Pubkey,
+    pub delegate: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct VotesUndelegated {
+    pub delegator: Pubkey,
+    pub delegate: Pubkey,
     pub timestamp: i64,
 }
 
 #[event]
 pub struct WinnerDeclared {
-    pub winning_proposal_id: u8,
-    pub proposal_info: String,
+    pub proposal_id: u64,
+    pub winning_option: u8,
+    pub option_label: String,
+    pub option_votes: u64,
+    pub option_weight: u64,
     pub total_votes: u64,
+    pub total_weight: u64,
     pub declared_by: Pubkey,
     pub timestamp: i64,
 }
 
+#[event]
+pub struct ProposalDefeated {
+    pub proposal_id: u64,
+    pub reason: DefeatReason,
+    pub total_votes: u64,
+    pub total_weight: u64,
+    pub quorum_votes: u64,
+    pub quorum_tokens: u64,
+    pub approval_threshold_bps: u16,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct VoteTokensWithdrawn {
+    pub proposal_id: u64,
+    pub voter: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ProposalDepositReleased {
+    pub proposal_id: u64,
+    pub amount: u64,
+    pub recipient: Pubkey,
+    pub slashed: bool,
+    pub timestamp: i64,
+}
+
 #[event]
 pub struct ProposalClosed {
-    pub proposal_id: u8,
+    pub proposal_id: u64,
     pub rent_recovered: u64,
     pub recovered_to: Pubkey,
+    pub vote_records_closed: u64,
     pub timestamp: i64,
 }
 
@@ -51,6 +343,7 @@ pub struct ProposalClosed {
 pub struct VoterAccountClosed {
     pub voter: Pubkey,
     pub rent_recovered_to: Pubkey,
+    pub vote_records_closed: u64,
     pub timestamp: i64,
 }
 
@@ -74,6 +367,15 @@ pub struct TokensPurchased {
 #[event]
 pub struct SolWithdrawn {
     pub authority: Pubkey,
+    pub recipient: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct TreasuryTokensWithdrawn {
+    pub authority: Pubkey,
+    pub recipient_token_account: Pubkey,
     pub amount: u64,
     pub timestamp: i64,
 }
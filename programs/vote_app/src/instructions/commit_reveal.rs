@@ -0,0 +1,225 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::hash::hashv;
+use anchor_spl::token::{self, Transfer as TokenTransfer};
+
+use crate::contexts::{CommitVote, RevealVote, SettleCommitment};
+use crate::errors::VoteError;
+use crate::events::*;
+use crate::instructions::governance::require_voting_open;
+use crate::state::ProposalState;
+
+/// Records a hidden vote on a commit-reveal proposal during its voting window and
+/// escrows the proposal's commit bond.
+pub fn commit_vote(ctx: Context<CommitVote>, proposal_id: u64, commitment: [u8; 32]) -> Result<()> {
+    let clock = Clock::get()?;
+    let proposal = &mut ctx.accounts.proposal_account;
+
+    require_voting_open(proposal, clock.unix_timestamp)?;
+    require!(
+        proposal.reveal_ends_at.is_some(),
+        VoteError::NotCommitRevealProposal
+    );
+
+    let bond = proposal.commit_bond;
+    if bond > 0 {
+        let cpi_accounts = TokenTransfer {
+            from: ctx.accounts.voter_token_account.to_account_info(),
+            to: ctx.accounts.proposal_vault.to_account_info(),
+            authority: ctx.accounts.authority.to_account_info(),
+        };
+        token::transfer(
+            CpiContext::new(ctx.accounts.token_program.to_account_info(), cpi_accounts),
+            bond,
+        )?;
+        proposal.escrowed_tokens = proposal
+            .escrowed_tokens
+            .checked_add(bond)
+            .ok_or(VoteError::ProposalWeightOverflow)?;
+    }
+    proposal.unrevealed_commitments = proposal
+        .unrevealed_commitments
+        .checked_add(1)
+        .ok_or(VoteError::ProposalVotesOverflow)?;
+
+    let vote_commitment = &mut ctx.accounts.commitment;
+    vote_commitment.proposal_id = proposal_id;
+    vote_commitment.voter = ctx.accounts.authority.key();
+    vote_commitment.commitment = commitment;
+    vote_commitment.bond = bond;
+    vote_commitment.committed_at = clock.unix_timestamp;
+    vote_commitment.revealed = false;
+
+    emit!(VoteCommitted {
+        voter: vote_commitment.voter,
+        proposal_id,
+        bond,
+        timestamp: clock.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+/// Opens a commitment between the proposal deadline and the end of its reveal window.
+/// The revealed tokens are escrowed and counted like a regular vote; the commit bond
+/// is added to the vote's locked tokens and released with them.
+pub fn reveal_vote(
+    ctx: Context<RevealVote>,
+    proposal_id: u64,
+    choice: u8,
+    tokens: u64,
+    salt: [u8; 32],
+) -> Result<()> {
+    let clock = Clock::get()?;
+    let proposal = &mut ctx.accounts.proposal_account;
+    let vote_commitment = &mut ctx.accounts.commitment;
+
+    let reveal_ends_at = proposal
+        .reveal_ends_at
+        .ok_or(VoteError::NotCommitRevealProposal)?;
+    require!(
+        proposal.state == ProposalState::Active,
+        VoteError::ProposalNotActive
+    );
+    require!(
+        clock.unix_timestamp >= proposal.deadline && clock.unix_timestamp < reveal_ends_at,
+        VoteError::RevealWindowNotOpen
+    );
+    require!(
+        !vote_commitment.revealed,
+        VoteError::CommitmentAlreadyRevealed
+    );
+    let preimage: [&[u8]; 5] = [
+        &proposal_id.to_le_bytes(),
+        vote_commitment.voter.as_ref(),
+        &[choice],
+        &tokens.to_le_bytes(),
+        &salt,
+    ];
+    require!(
+        hashv(&preimage).to_bytes() == vote_commitment.commitment,
+        VoteError::CommitmentMismatch
+    );
+    require!(
+        usize::from(choice) < proposal.options.len(),
+        VoteError::InvalidOptionIndex
+    );
+    require!(tokens > 0, VoteError::InvalidVoteWeight);
+
+    let cpi_accounts = TokenTransfer {
+        from: ctx.accounts.voter_token_account.to_account_info(),
+        to: ctx.accounts.proposal_vault.to_account_info(),
+        authority: ctx.accounts.authority.to_account_info(),
+    };
+    token::transfer(
+        CpiContext::new(ctx.accounts.token_program.to_account_info(), cpi_accounts),
+        tokens,
+    )?;
+
+    let weight = proposal.voting_mode.effective_votes(tokens);
+    let option_weight = proposal.add_vote(choice, tokens, weight)?;
+    proposal.escrowed_tokens = proposal
+        .escrowed_tokens
+        .checked_add(tokens)
+        .ok_or(VoteError::ProposalWeightOverflow)?;
+    proposal.unrevealed_commitments -= 1;
+    vote_commitment.revealed = true;
+
+    let authority = ctx.accounts.authority.key();
+    let vote_record = &mut ctx.accounts.vote_record;
+    vote_record.proposal_id = proposal_id;
+    vote_record.voter = authority;
+    vote_record.choice = choice;
+    vote_record.weight = weight;
+    vote_record.tokens = tokens;
+    vote_record.tokens_locked = tokens
+        .checked_add(vote_commitment.bond)
+        .ok_or(VoteError::ProposalWeightOverflow)?;
+    vote_record.delegated_tokens = 0;
+    vote_record.voted_at = clock.unix_timestamp;
+    vote_record.cast_by = authority;
+    vote_record.rent_payer = authority;
+
+    let voter_account = &mut ctx.accounts.voter_account;
+    voter_account.votes_cast = voter_account
+        .votes_cast
+        .checked_add(1)
+        .ok_or(VoteError::ProposalVotesOverflow)?;
+
+    emit!(VoteCast {
+        voter: authority,
+        cast_by: authority,
+        proposal_id,
+        choice,
+        tokens,
+        weight,
+        option_weight,
+        total_votes: proposal.number_of_votes,
+        total_weight: proposal.total_weight,
+        timestamp: clock.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+/// Closes a commitment once its proposal is settled. The bond of a commitment that was
+/// never revealed goes to the treasury, unless the proposal was cancelled before the
+/// reveal window, in which case it is refunded. Anyone can call it.
+pub fn settle_commitment(ctx: Context<SettleCommitment>, proposal_id: u64) -> Result<()> {
+    let proposal = &ctx.accounts.proposal_account;
+    let vote_commitment = &ctx.accounts.commitment;
+
+    require!(proposal.state.is_settled(), VoteError::ProposalNotSettled);
+
+    let bond = if vote_commitment.revealed {
+        0
+    } else {
+        vote_commitment.bond
+    };
+    let slashed = bond > 0 && proposal.state != ProposalState::Cancelled;
+    if bond > 0 {
+        let recipient = if slashed {
+            ctx.accounts.treasury_token_account.to_account_info()
+        } else {
+            ctx.accounts.voter_token_account.to_account_info()
+        };
+
+        let realm_key = ctx.accounts.realm.key();
+        let escrow_authority_seeds = &[
+            b"escrow_authority".as_ref(),
+            realm_key.as_ref(),
+            &[ctx.bumps.escrow_authority],
+        ];
+        let signer_seeds = &[&escrow_authority_seeds[..]];
+
+        let cpi_accounts = TokenTransfer {
+            from: ctx.accounts.proposal_vault.to_account_info(),
+            to: recipient,
+            authority: ctx.accounts.escrow_authority.to_account_info(),
+        };
+        token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                cpi_accounts,
+                signer_seeds,
+            ),
+            bond,
+        )?;
+
+        let proposal = &mut ctx.accounts.proposal_account;
+        proposal.escrowed_tokens = proposal
+            .escrowed_tokens
+            .checked_sub(bond)
+            .ok_or(VoteError::EscrowNotEmpty)?;
+    }
+
+    emit!(CommitmentSettled {
+        voter: vote_commitment.voter,
+        proposal_id,
+        revealed: vote_commitment.revealed,
+        bond,
+        slashed,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,225 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program::{self, Transfer as SolTransfer};
+
+use crate::contexts::{
+    CreateConvictionProposal, ExecuteConvictionProposal, SupportConvictionProposal,
+    WithdrawConvictionSupport,
+};
+use crate::errors::VoteError;
+use crate::events::*;
+use crate::state::{ConvictionProposal, BPS_DENOMINATOR};
+
+pub fn create_conviction_proposal(
+    ctx: Context<CreateConvictionProposal>,
+    proposal_info: String,
+    beneficiary: Pubkey,
+    requested_lamports: u64,
+) -> Result<()> {
+    let clock = Clock::get()?;
+    let params = &ctx.accounts.governance_config_account.params;
+    require!(
+        params.conviction_half_life > 0,
+        VoteError::ConvictionVotingDisabled
+    );
+    require!(requested_lamports > 0, VoteError::InvalidAmount);
+
+    let proposal_counter_account = &mut ctx.accounts.proposal_counter_account;
+    let conviction_proposal = &mut ctx.accounts.conviction_proposal;
+    conviction_proposal.proposal_id = proposal_counter_account.conviction_count;
+    conviction_proposal.authority = ctx.accounts.authority.key();
+    conviction_proposal.beneficiary = beneficiary;
+    conviction_proposal.requested_lamports = requested_lamports;
+    conviction_proposal.proposal_info = proposal_info;
+    conviction_proposal.staked_tokens = 0;
+    conviction_proposal.conviction = 0;
+    conviction_proposal.half_life = params.conviction_half_life;
+    conviction_proposal.weight_bps = params.conviction_weight_bps;
+    conviction_proposal.max_ratio_bps = params.conviction_max_ratio_bps;
+    conviction_proposal.last_updated_at = clock.unix_timestamp;
+    conviction_proposal.executed_at = None;
+
+    proposal_counter_account.conviction_count = proposal_counter_account
+        .conviction_count
+        .checked_add(1)
+        .ok_or(VoteError::ProposalCounterOverflow)?;
+
+    emit!(ConvictionProposalCreated {
+        proposal_id: conviction_proposal.proposal_id,
+        creator: conviction_proposal.authority,
+        beneficiary,
+        requested_lamports,
+        half_life: conviction_proposal.half_life,
+        timestamp: clock.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+/// Places `amount` of the signer's unallocated stake on the proposal. The stake stays
+/// in the stake vault, but cannot be unstaked while it supports a proposal.
+pub fn support_conviction_proposal(
+    ctx: Context<SupportConvictionProposal>,
+    proposal_id: u64,
+    amount: u64,
+) -> Result<()> {
+    let clock = Clock::get()?;
+    let conviction_proposal = &mut ctx.accounts.conviction_proposal;
+    let stake_position = &mut ctx.accounts.stake_position;
+
+    require!(
+        conviction_proposal.executed_at.is_none(),
+        VoteError::ConvictionProposalAlreadyExecuted
+    );
+    require!(amount > 0, VoteError::InvalidAmount);
+    let unallocated = stake_position
+        .amount
+        .saturating_sub(stake_position.conviction_allocated);
+    require!(amount <= unallocated, VoteError::InsufficientStake);
+
+    conviction_proposal.accrue(clock.unix_timestamp);
+    conviction_proposal.staked_tokens = conviction_proposal
+        .staked_tokens
+        .checked_add(amount)
+        .ok_or(VoteError::ProposalWeightOverflow)?;
+    stake_position.conviction_allocated += amount;
+
+    let conviction_support = &mut ctx.accounts.conviction_support;
+    conviction_support.proposal_id = proposal_id;
+    conviction_support.supporter = ctx.accounts.authority.key();
+    conviction_support.amount += amount;
+    conviction_support.bump = ctx.bumps.conviction_support;
+
+    emit!(ConvictionSupportUpdated {
+        proposal_id,
+        supporter: conviction_support.supporter,
+        supporter_amount: conviction_support.amount,
+        staked_tokens: conviction_proposal.staked_tokens,
+        conviction: conviction_proposal.conviction,
+        timestamp: clock.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+/// Takes `amount` of the signer's support back off the proposal, closing its support
+/// account once nothing is left on it.
+pub fn withdraw_conviction_support(
+    ctx: Context<WithdrawConvictionSupport>,
+    proposal_id: u64,
+    amount: u64,
+) -> Result<()> {
+    let clock = Clock::get()?;
+    let conviction_proposal = &mut ctx.accounts.conviction_proposal;
+    let conviction_support = &mut ctx.accounts.conviction_support;
+
+    require!(
+        amount > 0 && amount <= conviction_support.amount,
+        VoteError::InsufficientStake
+    );
+
+    conviction_proposal.accrue(clock.unix_timestamp);
+    conviction_proposal.staked_tokens -= amount;
+    conviction_support.amount -= amount;
+    ctx.accounts.stake_position.conviction_allocated -= amount;
+
+    emit!(ConvictionSupportUpdated {
+        proposal_id,
+        supporter: conviction_support.supporter,
+        supporter_amount: conviction_support.amount,
+        staked_tokens: conviction_proposal.staked_tokens,
+        conviction: conviction_proposal.conviction,
+        timestamp: clock.unix_timestamp,
+    });
+
+    if conviction_support.amount == 0 {
+        conviction_support.close(ctx.accounts.authority.to_account_info())?;
+    }
+
+    Ok(())
+}
+
+/// Pays the requested lamports out of the SOL vault once the proposal's conviction has
+/// reached its threshold. Anyone can call it.
+pub fn execute_conviction_proposal(
+    ctx: Context<ExecuteConvictionProposal>,
+    proposal_id: u64,
+) -> Result<()> {
+    let clock = Clock::get()?;
+    let conviction_proposal = &mut ctx.accounts.conviction_proposal;
+
+    require!(
+        conviction_proposal.executed_at.is_none(),
+        VoteError::ConvictionProposalAlreadyExecuted
+    );
+    conviction_proposal.accrue(clock.unix_timestamp);
+
+    let available = ctx
+        .accounts
+        .sol_vault
+        .lamports()
+        .saturating_sub(Rent::get()?.minimum_balance(0));
+    let threshold =
+        conviction_threshold(conviction_proposal, ctx.accounts.x_mint.supply, available)?;
+    require!(
+        conviction_proposal.staked_tokens > 0 && conviction_proposal.conviction >= threshold,
+        VoteError::ConvictionThresholdNotReached
+    );
+
+    let realm_key = ctx.accounts.realm.key();
+    let sol_vault_seeds = &[
+        b"sol_vault".as_ref(),
+        realm_key.as_ref(),
+        &[ctx.accounts.treasury_config.bump],
+    ];
+    let signer_seeds = &[&sol_vault_seeds[..]];
+
+    system_program::transfer(
+        CpiContext::new_with_signer(
+            ctx.accounts.system_program.to_account_info(),
+            SolTransfer {
+                from: ctx.accounts.sol_vault.to_account_info(),
+                to: ctx.accounts.beneficiary.to_account_info(),
+            },
+            signer_seeds,
+        ),
+        conviction_proposal.requested_lamports,
+    )?;
+
+    conviction_proposal.executed_at = Some(clock.unix_timestamp);
+
+    emit!(ConvictionProposalExecuted {
+        proposal_id,
+        beneficiary: conviction_proposal.beneficiary,
+        amount: conviction_proposal.requested_lamports,
+        conviction: conviction_proposal.conviction,
+        threshold,
+        timestamp: clock.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+/// Conviction needed to pay out: `supply * weight / (1 - share / max_ratio)^2`, where
+/// `share` is the requested fraction of the available treasury. Requests of
+/// `max_ratio` or more of the treasury can never pass, and the threshold is never
+/// below one token.
+fn conviction_threshold(
+    conviction_proposal: &ConvictionProposal,
+    supply: u64,
+    available: u64,
+) -> Result<u64> {
+    require!(available > 0, VoteError::RequestExceedsMaxRatio);
+    let share_bps = u128::from(conviction_proposal.requested_lamports)
+        * u128::from(BPS_DENOMINATOR)
+        / u128::from(available);
+    let max_ratio_bps = u128::from(conviction_proposal.max_ratio_bps);
+    require!(share_bps < max_ratio_bps, VoteError::RequestExceedsMaxRatio);
+
+    let headroom_bps = max_ratio_bps - share_bps;
+    let threshold = u128::from(supply)
+        * u128::from(conviction_proposal.weight_bps)
+        * max_ratio_bps
+        * max_ratio_bps
+        / (u128::from(BPS_DENOMINATOR) * headroom_bps * headroom_bps);
+    Ok(u64::try_from(threshold).unwrap_or(u64::MAX).max(1))
+}
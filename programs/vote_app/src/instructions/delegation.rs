@@ -0,0 +1,94 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::program_option::COption;
+use anchor_spl::token::{self, Token, TokenAccount};
+
+use crate::contexts::{DelegateVotes, UndelegateVotes};
+use crate::events::*;
+use crate::state::Delegation;
+
+/// Lets `delegate` vote with up to `amount` of the signer's tokens. The delegation PDA
+/// is approved as SPL delegate of the signer's token account, so the tokens stay in
+/// the delegator's wallet until a delegate actually votes with them. Tokens a delegate
+/// escrows come back under the delegation when they are refunded, so the delegation
+/// keeps working for later proposals.
+pub fn delegate_votes(ctx: Context<DelegateVotes>, amount: u64) -> Result<()> {
+    let clock = Clock::get()?;
+
+    let cpi_accounts = token::Approve {
+        to: ctx.accounts.delegator_token_account.to_account_info(),
+        delegate: ctx.accounts.delegation.to_account_info(),
+        authority: ctx.accounts.authority.to_account_info(),
+    };
+    token::approve(
+        CpiContext::new(ctx.accounts.token_program.to_account_info(), cpi_accounts),
+        amount,
+    )?;
+
+    let delegation = &mut ctx.accounts.delegation;
+    delegation.delegator = ctx.accounts.authority.key();
+    delegation.delegate = ctx.accounts.delegate.key();
+    delegation.amount = amount;
+    delegation.delegated_at = clock.unix_timestamp;
+    delegation.bump = ctx.bumps.delegation;
+
+    emit!(VotesDelegated {
+        delegator: delegation.delegator,
+        delegate: delegation.delegate,
+        amount,
+        timestamp: clock.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+pub fn undelegate_votes(ctx: Context<UndelegateVotes>) -> Result<()> {
+    let cpi_accounts = token::Revoke {
+        source: ctx.accounts.delegator_token_account.to_account_info(),
+        authority: ctx.accounts.authority.to_account_info(),
+    };
+    token::revoke(CpiContext::new(
+        ctx.accounts.token_program.to_account_info(),
+        cpi_accounts,
+    ))?;
+
+    emit!(VotesUndelegated {
+        delegator: ctx.accounts.delegation.delegator,
+        delegate: ctx.accounts.delegation.delegate,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+/// Approves `refunded` tokens, which a delegate had escrowed from the signer's wallet,
+/// back to the signer's delegation. The allowance never grows past the delegated
+/// amount. Does nothing when no delegation is passed.
+pub(crate) fn restore_delegated_allowance<'info>(
+    delegation: Option<&Account<'info, Delegation>>,
+    token_account: &Account<'info, TokenAccount>,
+    authority: &Signer<'info>,
+    token_program: &Program<'info, Token>,
+    refunded: u64,
+) -> Result<()> {
+    let Some(delegation) = delegation else {
+        return Ok(());
+    };
+    if refunded == 0 {
+        return Ok(());
+    }
+
+    let allowance = if token_account.delegate == COption::Some(delegation.key()) {
+        token_account.delegated_amount
+    } else {
+        0
+    };
+    let cpi_accounts = token::Approve {
+        to: token_account.to_account_info(),
+        delegate: delegation.to_account_info(),
+        authority: authority.to_account_info(),
+    };
+    token::approve(
+        CpiContext::new(token_program.to_account_info(), cpi_accounts),
+        allowance.saturating_add(refunded).min(delegation.amount),
+    )
+}
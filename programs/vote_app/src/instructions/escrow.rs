@@ -0,0 +1,124 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Transfer as TokenTransfer};
+
+use crate::contexts::{ReclaimProposalDeposit, WithdrawVoteTokens};
+use crate::errors::VoteError;
+use crate::events::*;
+use crate::instructions::delegation::restore_delegated_allowance;
+use crate::state::DefeatReason;
+
+pub fn withdraw_vote_tokens(ctx: Context<WithdrawVoteTokens>, proposal_id: u64) -> Result<()> {
+    let proposal = &ctx.accounts.proposal_account;
+    let amount = ctx.accounts.vote_record.tokens_locked;
+
+    require!(proposal.state.is_settled(), VoteError::ProposalNotSettled);
+    require!(amount > 0, VoteError::NothingToWithdraw);
+
+    let realm_key = ctx.accounts.realm.key();
+    let escrow_authority_seeds = &[
+        b"escrow_authority".as_ref(),
+        realm_key.as_ref(),
+        &[ctx.bumps.escrow_authority],
+    ];
+    let signer_seeds = &[&escrow_authority_seeds[..]];
+
+    let cpi_accounts = TokenTransfer {
+        from: ctx.accounts.proposal_vault.to_account_info(),
+        to: ctx.accounts.voter_token_account.to_account_info(),
+        authority: ctx.accounts.escrow_authority.to_account_info(),
+    };
+    token::transfer(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            cpi_accounts,
+            signer_seeds,
+        ),
+        amount,
+    )?;
+    restore_delegated_allowance(
+        ctx.accounts.delegation.as_ref(),
+        &ctx.accounts.voter_token_account,
+        &ctx.accounts.authority,
+        &ctx.accounts.token_program,
+        ctx.accounts.vote_record.delegated_tokens,
+    )?;
+
+    ctx.accounts.vote_record.tokens_locked = 0;
+    ctx.accounts.vote_record.delegated_tokens = 0;
+
+    let proposal = &mut ctx.accounts.proposal_account;
+    proposal.escrowed_tokens = proposal
+        .escrowed_tokens
+        .checked_sub(amount)
+        .ok_or(VoteError::EscrowNotEmpty)?;
+
+    emit!(VoteTokensWithdrawn {
+        proposal_id,
+        voter: ctx.accounts.authority.key(),
+        amount,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+/// Releases the creator's deposit once the proposal is settled. When the DAO slashes
+/// spam proposals and this one was defeated for missing quorum, the deposit goes to the
+/// treasury instead of back to the creator.
+pub fn reclaim_proposal_deposit(
+    ctx: Context<ReclaimProposalDeposit>,
+    proposal_id: u64,
+) -> Result<()> {
+    let proposal = &ctx.accounts.proposal_account;
+    let amount = proposal.deposit_amount;
+
+    require!(proposal.state.is_settled(), VoteError::ProposalNotSettled);
+    require!(amount > 0, VoteError::NothingToWithdraw);
+
+    let slashed = proposal.slash_failed_quorum_deposit
+        && proposal.defeat_reason == Some(DefeatReason::QuorumNotReached);
+    let recipient = if slashed {
+        ctx.accounts.treasury_token_account.to_account_info()
+    } else {
+        ctx.accounts.creator_token_account.to_account_info()
+    };
+
+    let realm_key = ctx.accounts.realm.key();
+    let escrow_authority_seeds = &[
+        b"escrow_authority".as_ref(),
+        realm_key.as_ref(),
+        &[ctx.bumps.escrow_authority],
+    ];
+    let signer_seeds = &[&escrow_authority_seeds[..]];
+
+    let cpi_accounts = TokenTransfer {
+        from: ctx.accounts.proposal_vault.to_account_info(),
+        to: recipient.clone(),
+        authority: ctx.accounts.escrow_authority.to_account_info(),
+    };
+    token::transfer(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            cpi_accounts,
+            signer_seeds,
+        ),
+        amount,
+    )?;
+
+    let proposal = &mut ctx.accounts.proposal_account;
+    proposal.deposit_amount = 0;
+    proposal.escrowed_tokens = proposal
+        .escrowed_tokens
+        .checked_sub(amount)
+        .ok_or(VoteError::EscrowNotEmpty)?;
+
+    emit!(ProposalDepositReleased {
+        proposal_id,
+        amount,
+        recipient: recipient.key(),
+        slashed,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
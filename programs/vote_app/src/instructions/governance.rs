@@ -1,9 +1,22 @@
 use anchor_lang::prelude::*;
-use anchor_spl::token::{self, Transfer as TokenTransfer};
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::solana_program::program::invoke_signed;
+use anchor_lang::solana_program::program_option::COption;
+use anchor_lang::system_program;
+use anchor_lang::Discriminator;
+use anchor_spl::token::{self, TokenAccount, Transfer as TokenTransfer};
 
 use crate::contexts::*;
 use crate::errors::VoteError;
 use crate::events::*;
+use crate::instructions::delegation::restore_delegated_allowance;
+use crate::state::{
+    AntiSnipingPolicy, DefeatReason, Delegation, GovernanceParams, InstructionAccount,
+    LegacyProposal, Proposal, ProposalOption, ProposalState, TiePolicy, VoteRecord, VotingMode,
+    BPS_DENOMINATOR, LEGACY_OPTION_LABEL, MAX_INSTRUCTION_ACCOUNTS, MAX_INSTRUCTION_DATA_LEN,
+    MAX_OPTION_LABEL_LEN, MAX_PROPOSAL_INSTRUCTIONS, MAX_PROPOSAL_OPTIONS, MAX_ROUND_PROPOSALS,
+    MIN_PROPOSAL_OPTIONS,
+};
 
 pub fn initialize_proposal_counter(ctx: Context<InitializeProposalCounter>) -> Result<()> {
     let proposal_counter_account = &mut ctx.accounts.proposal_counter_account;
@@ -13,6 +26,8 @@ pub fn initialize_proposal_counter(ctx: Context<InitializeProposalCounter>) -> R
     );
 
     proposal_counter_account.proposal_count = 1;
+    proposal_counter_account.round_count = 1;
+    proposal_counter_account.conviction_count = 1;
     proposal_counter_account.authority = ctx.accounts.authority.key();
 
     emit!(ProposalCounterInitialized {
@@ -23,6 +38,44 @@ pub fn initialize_proposal_counter(ctx: Context<InitializeProposalCounter>) -> R
     Ok(())
 }
 
+pub fn initialize_governance_config(
+    ctx: Context<InitializeGovernanceConfig>,
+    params: GovernanceParams,
+) -> Result<()> {
+    validate_governance_params(&params)?;
+
+    let governance_config_account = &mut ctx.accounts.governance_config_account;
+    governance_config_account.authority = ctx.accounts.authority.key();
+    governance_config_account.params = params.clone();
+    governance_config_account.bump = ctx.bumps.governance_config_account;
+
+    emit!(GovernanceConfigUpdated {
+        authority: ctx.accounts.authority.key(),
+        params,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+pub fn update_governance_config(
+    ctx: Context<UpdateGovernanceConfig>,
+    params: GovernanceParams,
+) -> Result<()> {
+    validate_governance_params(&params)?;
+
+    let governance_config_account = &mut ctx.accounts.governance_config_account;
+    governance_config_account.params = params.clone();
+
+    emit!(GovernanceConfigUpdated {
+        authority: ctx.accounts.authority.key(),
+        params,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
+
 pub fn register_voter(ctx: Context<RegisterVoter>) -> Result<()> {
     let voter_account = &mut ctx.accounts.voter_account;
     voter_account.voter_id = ctx.accounts.authority.key();
@@ -39,17 +92,38 @@ pub fn register_voter(ctx: Context<RegisterVoter>) -> Result<()> {
 pub fn register_proposal(
     ctx: Context<RegisterProposal>,
     proposal_info: String,
+    options: Vec<String>,
     deadline: i64,
     token_amount: u64,
 ) -> Result<()> {
     let clock = Clock::get()?;
-    require!(deadline > clock.unix_timestamp, VoteError::InvalidDeadline);
+    let params = &ctx.accounts.governance_config_account.params;
+    let voting_starts_at = clock
+        .unix_timestamp
+        .checked_add(params.voting_delay)
+        .ok_or(VoteError::InvalidDeadline)?;
+    require!(deadline > voting_starts_at, VoteError::InvalidDeadline);
+    let voting_period = deadline - voting_starts_at;
+    require!(
+        (params.min_voting_period..=params.max_voting_period).contains(&voting_period),
+        VoteError::InvalidVotingPeriod
+    );
+    require!(
+        (MIN_PROPOSAL_OPTIONS..=MAX_PROPOSAL_OPTIONS).contains(&options.len()),
+        VoteError::InvalidOptionCount
+    );
+    require!(
+        options
+            .iter()
+            .all(|label| !label.trim().is_empty() && label.len() <= MAX_OPTION_LABEL_LEN),
+        VoteError::InvalidOptionLabel
+    );
 
     let proposal_account = &mut ctx.accounts.proposal_account;
 
     let cpi_accounts = TokenTransfer {
         from: ctx.accounts.proposal_token_account.to_account_info(),
-        to: ctx.accounts.treasury_token_account.to_account_info(),
+        to: ctx.accounts.proposal_vault.to_account_info(),
         authority: ctx.accounts.authority.to_account_info(),
     };
 
@@ -57,8 +131,52 @@ pub fn register_proposal(
     token::transfer(cpi_ctx, token_amount)?;
 
     proposal_account.proposal_info = proposal_info;
+    proposal_account.voting_starts_at = voting_starts_at;
     proposal_account.deadline = deadline;
     proposal_account.authority = ctx.accounts.authority.key();
+    proposal_account.options = options
+        .iter()
+        .map(|label| ProposalOption {
+            label: label.clone(),
+            votes: 0,
+            weight: 0,
+        })
+        .collect();
+    proposal_account.winning_option = None;
+    proposal_account.state = ProposalState::Draft;
+
+    proposal_account.voting_mode = params.voting_mode;
+    proposal_account.quorum_votes = params.min_quorum_votes;
+    let quorum_tokens = u64::try_from(
+        u128::from(ctx.accounts.x_mint.supply) * u128::from(params.quorum_supply_bps)
+            / u128::from(BPS_DENOMINATOR),
+    )
+    .map_err(|_| VoteError::ProposalWeightOverflow)?;
+    proposal_account.quorum_tokens = quorum_tokens;
+    proposal_account.approval_threshold_bps = params.approval_threshold_bps;
+    proposal_account.instruction_count = 0;
+    proposal_account.instructions_executed = 0;
+    proposal_account.defeat_reason = None;
+    proposal_account.deposit_amount = token_amount;
+    proposal_account.escrowed_tokens = token_amount;
+    proposal_account.slash_failed_quorum_deposit = params.slash_failed_quorum_deposits;
+    proposal_account.round_id = None;
+    proposal_account.tie_policy = params.tie_policy;
+    proposal_account.snapshot_at = voting_starts_at;
+    proposal_account.snapshot_voting = params.snapshot_voting;
+    proposal_account.reveal_ends_at = if params.commit_reveal {
+        Some(
+            deadline
+                .checked_add(params.reveal_period)
+                .ok_or(VoteError::InvalidDeadline)?,
+        )
+    } else {
+        None
+    };
+    proposal_account.commit_bond = params.commit_bond;
+    proposal_account.unrevealed_commitments = 0;
+    proposal_account.anti_sniping = params.anti_sniping;
+    proposal_account.extensions = 0;
 
     let proposal_counter_account = &mut ctx.accounts.proposal_counter_account;
     proposal_account.proposal_id = proposal_counter_account.proposal_count;
@@ -67,74 +185,1029 @@ pub fn register_proposal(
         .checked_add(1)
         .ok_or(VoteError::ProposalCounterOverflow)?;
 
+    if let Some(voting_round) = ctx.accounts.voting_round.as_mut() {
+        require!(
+            ctx.accounts
+                .round_authority
+                .as_ref()
+                .is_some_and(|round_authority| round_authority.key() == voting_round.authority),
+            VoteError::UnauthorizedAccess
+        );
+        require!(
+            voting_round.is_open(clock.unix_timestamp),
+            VoteError::VotingRoundClosed
+        );
+        require!(
+            voting_round.runoff_of.is_none(),
+            VoteError::InvalidVotingRound
+        );
+        require!(deadline <= voting_round.ends_at, VoteError::InvalidDeadline);
+        require!(
+            voting_round.proposal_ids.len() < MAX_ROUND_PROPOSALS,
+            VoteError::VotingRoundFull
+        );
+        voting_round.proposal_ids.push(proposal_account.proposal_id);
+        proposal_account.round_id = Some(voting_round.round_id);
+    }
+
     emit!(ProposalCreated {
         proposal_id: proposal_account.proposal_id,
         creator: proposal_account.authority,
         proposal_info: proposal_account.proposal_info.clone(),
+        options,
+        voting_starts_at,
         deadline: proposal_account.deadline,
+        round_id: proposal_account.round_id,
+        snapshot_at: proposal_account.snapshot_at,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+    Ok(())
+}
+
+/// Re-creates a proposal stored under the pre-realm `[b"proposal", u8]` seed as a
+/// `Migrated` proposal with the realm's next `u64` id. Legacy proposals had a single
+/// implicit option, so their vote count is carried over as that option's tally. The
+/// original creator must co-sign, so a proposal can only be moved into a realm its
+/// creator chose. The legacy account is closed and its rent refunded to the creator.
+pub fn migrate_legacy_proposal(
+    ctx: Context<MigrateLegacyProposal>,
+    legacy_proposal_id: u8,
+) -> Result<()> {
+    let legacy_info = ctx.accounts.legacy_proposal.to_account_info();
+    require_keys_eq!(
+        *legacy_info.owner,
+        crate::ID,
+        VoteError::InvalidLegacyProposal
+    );
+
+    let legacy = {
+        let data = legacy_info.try_borrow_data()?;
+        require!(
+            data.len() > 8 && data[..8] == Proposal::DISCRIMINATOR,
+            VoteError::InvalidLegacyProposal
+        );
+        LegacyProposal::deserialize(&mut &data[8..])
+            .map_err(|_| VoteError::InvalidLegacyProposal)?
+    };
+    require!(
+        legacy.proposal_id == legacy_proposal_id,
+        VoteError::InvalidLegacyProposal
+    );
+    require_keys_eq!(
+        legacy.authority,
+        ctx.accounts.legacy_authority.key(),
+        VoteError::InvalidLegacyProposal
+    );
+
+    let proposal_account = &mut ctx.accounts.proposal_account;
+    let proposal_counter_account = &mut ctx.accounts.proposal_counter_account;
+    proposal_account.proposal_id = proposal_counter_account.proposal_count;
+    proposal_counter_account.proposal_count = proposal_counter_account
+        .proposal_count
+        .checked_add(1)
+        .ok_or(VoteError::ProposalCounterOverflow)?;
+
+    proposal_account.number_of_votes = legacy.number_of_votes;
+    proposal_account.total_weight = legacy.number_of_votes;
+    proposal_account.total_tokens = legacy.number_of_votes;
+    proposal_account.voting_starts_at = 0;
+    proposal_account.deadline = legacy.deadline;
+    proposal_account.voting_mode = VotingMode::TokenWeighted;
+    proposal_account.proposal_info = legacy.proposal_info;
+    proposal_account.authority = legacy.authority;
+    proposal_account.options = vec![ProposalOption {
+        label: LEGACY_OPTION_LABEL.to_string(),
+        votes: legacy.number_of_votes,
+        weight: legacy.number_of_votes,
+    }];
+    proposal_account.winning_option = None;
+    proposal_account.state = ProposalState::Migrated;
+    proposal_account.quorum_votes = 0;
+    proposal_account.quorum_tokens = 0;
+    proposal_account.approval_threshold_bps = 0;
+    proposal_account.instruction_count = 0;
+    proposal_account.instructions_executed = 0;
+    proposal_account.defeat_reason = None;
+    proposal_account.deposit_amount = 0;
+    proposal_account.escrowed_tokens = 0;
+    proposal_account.slash_failed_quorum_deposit = false;
+    proposal_account.round_id = None;
+    proposal_account.tie_policy = TiePolicy::EarliestCreated;
+    proposal_account.snapshot_at = 0;
+    proposal_account.snapshot_voting = false;
+    proposal_account.reveal_ends_at = None;
+    proposal_account.commit_bond = 0;
+    proposal_account.unrevealed_commitments = 0;
+    proposal_account.anti_sniping = AntiSnipingPolicy::default();
+    proposal_account.extensions = 0;
+
+    let legacy_authority = ctx.accounts.legacy_authority.to_account_info();
+    **legacy_authority.try_borrow_mut_lamports()? = legacy_authority
+        .lamports()
+        .checked_add(legacy_info.lamports())
+        .ok_or(VoteError::ProposalWeightOverflow)?;
+    **legacy_info.try_borrow_mut_lamports()? = 0;
+    legacy_info.assign(&anchor_lang::system_program::ID);
+    legacy_info.realloc(0, false)?;
+
+    emit!(LegacyProposalMigrated {
+        legacy_proposal_id,
+        proposal_id: proposal_account.proposal_id,
+        legacy_proposal: legacy_info.key(),
+        migrated_by: ctx.accounts.authority.key(),
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+pub fn add_proposal_instruction(
+    ctx: Context<AddProposalInstruction>,
+    proposal_id: u64,
+    program_id: Pubkey,
+    accounts: Vec<InstructionAccount>,
+    data: Vec<u8>,
+) -> Result<()> {
+    let proposal = &mut ctx.accounts.proposal_account;
+
+    require!(
+        proposal.state == ProposalState::Draft,
+        VoteError::ProposalNotDraft
+    );
+    require!(
+        proposal.instruction_count < MAX_PROPOSAL_INSTRUCTIONS,
+        VoteError::TooManyProposalInstructions
+    );
+    require!(
+        accounts.len() <= MAX_INSTRUCTION_ACCOUNTS && data.len() <= MAX_INSTRUCTION_DATA_LEN,
+        VoteError::InvalidProposalInstruction
+    );
+
+    let proposal_instruction = &mut ctx.accounts.proposal_instruction;
+    proposal_instruction.proposal_id = proposal_id;
+    proposal_instruction.index = proposal.instruction_count;
+    proposal_instruction.program_id = program_id;
+    proposal_instruction.accounts = accounts;
+    proposal_instruction.data = data;
+    proposal_instruction.executed_at = None;
+
+    proposal.instruction_count += 1;
+
+    emit!(ProposalInstructionAdded {
+        proposal_id,
+        index: proposal_instruction.index,
+        program_id,
         timestamp: Clock::get()?.unix_timestamp,
     });
+
+    Ok(())
+}
+
+pub fn activate_proposal(ctx: Context<ActivateProposal>, proposal_id: u64) -> Result<()> {
+    let clock = Clock::get()?;
+    let proposal = &mut ctx.accounts.proposal_account;
+
+    require!(
+        proposal.deadline > clock.unix_timestamp,
+        VoteError::InvalidDeadline
+    );
+    proposal.transition_to(ProposalState::Active)?;
+
+    emit!(ProposalActivated {
+        proposal_id,
+        activated_by: ctx.accounts.authority.key(),
+        deadline: proposal.deadline,
+        timestamp: clock.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+pub fn cancel_proposal(ctx: Context<CancelProposal>, proposal_id: u64) -> Result<()> {
+    let clock = Clock::get()?;
+    let proposal = &mut ctx.accounts.proposal_account;
+    let previous_state = proposal.state;
+
+    if previous_state == ProposalState::Active {
+        require!(
+            proposal.deadline > clock.unix_timestamp,
+            VoteError::ProposalEnded
+        );
+    }
+    proposal.transition_to(ProposalState::Cancelled)?;
+
+    emit!(ProposalCancelled {
+        proposal_id,
+        previous_state,
+        cancelled_by: ctx.accounts.authority.key(),
+        timestamp: clock.unix_timestamp,
+    });
+
     Ok(())
 }
 
-pub fn proposal_to_vote(ctx: Context<Vote>, proposal_id: u8, token_amount: u64) -> Result<()> {
+/// Casts the signer's vote. A delegate also votes for the holders that delegated to it
+/// by passing `(delegation, vote_record, token_account)` triples in `remaining_accounts`;
+/// the tokens each delegator approved are escrowed on its behalf and a vote record is
+/// created for it. Delegators that already hold a vote record are skipped. A delegate
+/// that has already voted can call again with only delegator triples and no tokens to
+/// cast for more delegators with the same choice. A delegator
+/// can override a vote cast on its behalf by voting directly before the deadline; the
+/// tokens already escrowed for it are added to the new vote. A voter that passes its
+/// stake position votes with the power the position had at the proposal snapshot on
+/// top of any tokens it escrows; staked tokens stay in the stake vault. Proposals with
+/// snapshot voting only accept that staked power.
+pub fn proposal_to_vote<'info>(
+    ctx: Context<'_, '_, 'info, 'info, Vote<'info>>,
+    proposal_id: u64,
+    choice: u8,
+    token_amount: u64,
+) -> Result<()> {
     let clock = Clock::get()?;
+    let authority = ctx.accounts.authority.key();
     let proposal_account = &mut ctx.accounts.proposal_account;
+    let vote_record = &mut ctx.accounts.vote_record;
 
+    require_voting_open(proposal_account, clock.unix_timestamp)?;
+    require!(
+        proposal_account.reveal_ends_at.is_none(),
+        VoteError::CommitRevealRequired
+    );
+    if proposal_account.snapshot_voting {
+        require!(
+            ctx.accounts.stake_position.is_some()
+                && token_amount == 0
+                && ctx.remaining_accounts.is_empty(),
+            VoteError::SnapshotVotingRequiresStake
+        );
+    }
+    // The supply quorum counts the staked amount itself; only the weight is scaled by
+    // the time left on the lock.
+    let (staked_amount, staked_power) = match ctx.accounts.stake_position.as_ref() {
+        Some(stake_position) => {
+            let checkpoint = stake_position
+                .checkpoint_before(proposal_account.snapshot_at)
+                .ok_or(VoteError::NoStakeAtSnapshot)?;
+            let power = checkpoint.voting_power_at(proposal_account.deadline);
+            require!(power > 0, VoteError::StakeExpiresBeforeDeadline);
+            (checkpoint.amount, power)
+        }
+        None => (0, 0),
+    };
     require!(
-        proposal_account.deadline > clock.unix_timestamp,
-        VoteError::ProposalEnded
+        usize::from(choice) < proposal_account.options.len(),
+        VoteError::InvalidOptionIndex
+    );
+    require!(
+        ctx.remaining_accounts.len() % 3 == 0,
+        VoteError::InvalidRemainingAccounts
     );
 
-    let cpi_accounts = TokenTransfer {
-        from: ctx.accounts.voter_token_account.to_account_info(),
-        to: ctx.accounts.treasury_token_account.to_account_info(),
-        authority: ctx.accounts.authority.to_account_info(),
+    // A delegate that has already voted can come back with more delegators; its own
+    // vote is left as it is.
+    let overriding = vote_record.voter != Pubkey::default();
+    let delegate_batch = overriding && vote_record.cast_by == authority;
+    let casts_own_vote = overriding || token_amount > 0 || staked_power > 0;
+    if delegate_batch {
+        require!(
+            token_amount == 0 && !ctx.remaining_accounts.is_empty(),
+            VoteError::VoterAlreadyVoted
+        );
+        require!(
+            vote_record.choice == choice,
+            VoteError::DelegateChoiceMismatch
+        );
+    } else if !overriding {
+        require!(
+            casts_own_vote || !ctx.remaining_accounts.is_empty(),
+            VoteError::InvalidVoteWeight
+        );
+    }
+
+    if token_amount > 0 {
+        let cpi_accounts = TokenTransfer {
+            from: ctx.accounts.voter_token_account.to_account_info(),
+            to: ctx.accounts.proposal_vault.to_account_info(),
+            authority: ctx.accounts.authority.to_account_info(),
+        };
+
+        let cpi_ctx = CpiContext::new(ctx.accounts.token_program.to_account_info(), cpi_accounts);
+        token::transfer(cpi_ctx, token_amount)?;
+    }
+
+    let leader_before = proposal_account.leading_option();
+    let total_weight_before = proposal_account.total_weight;
+    let mut weight_cast = 0u64;
+
+    if !casts_own_vote {
+        // A delegate without weight of its own only casts for its delegators. Its empty
+        // record just holds the rent it paid until the proposal is closed.
+        vote_record.proposal_id = proposal_id;
+        vote_record.rent_payer = authority;
+    } else if !delegate_batch {
+        if overriding {
+            proposal_account.remove_vote(
+                vote_record.choice,
+                vote_record.tokens,
+                vote_record.weight,
+            )?;
+        }
+        let tokens_locked = vote_record
+            .tokens_locked
+            .checked_add(token_amount)
+            .ok_or(VoteError::ProposalWeightOverflow)?;
+        let tokens = tokens_locked
+            .checked_add(staked_amount)
+            .ok_or(VoteError::ProposalWeightOverflow)?;
+        let weight = proposal_account.voting_mode.effective_votes(
+            tokens_locked
+                .checked_add(staked_power)
+                .ok_or(VoteError::ProposalWeightOverflow)?,
+        );
+
+        vote_record.proposal_id = proposal_id;
+        vote_record.voter = authority;
+        vote_record.choice = choice;
+        vote_record.weight = weight;
+        vote_record.tokens = tokens;
+        vote_record.tokens_locked = tokens_locked;
+        vote_record.voted_at = clock.unix_timestamp;
+        vote_record.cast_by = authority;
+        if !overriding {
+            vote_record.rent_payer = authority;
+        }
+
+        let voter_account = &mut ctx.accounts.voter_account;
+        voter_account.votes_cast = voter_account
+            .votes_cast
+            .checked_add(1)
+            .ok_or(VoteError::ProposalVotesOverflow)?;
+
+        let option_weight = proposal_account.add_vote(choice, tokens, weight)?;
+        weight_cast = weight_cast.saturating_add(weight);
+        proposal_account.escrowed_tokens = proposal_account
+            .escrowed_tokens
+            .checked_add(token_amount)
+            .ok_or(VoteError::ProposalWeightOverflow)?;
+
+        emit!(VoteCast {
+            voter: authority,
+            cast_by: authority,
+            proposal_id,
+            choice,
+            tokens,
+            weight,
+            option_weight,
+            total_votes: proposal_account.number_of_votes,
+            total_weight: proposal_account.total_weight,
+            timestamp: clock.unix_timestamp,
+        });
+    }
+
+    let delegated_votes = DelegatedVoteAccounts {
+        realm: ctx.accounts.realm.key(),
+        x_mint: ctx.accounts.x_mint.key(),
+        voting_mode: proposal_account.voting_mode,
+        delegate: ctx.accounts.authority.to_account_info(),
+        proposal_vault: ctx.accounts.proposal_vault.to_account_info(),
+        token_program: ctx.accounts.token_program.to_account_info(),
+        system_program: ctx.accounts.system_program.to_account_info(),
     };
+    for accounts in ctx.remaining_accounts.chunks(3) {
+        let Some((delegator, tokens, weight)) =
+            delegated_votes.escrow(accounts, proposal_id, choice, clock.unix_timestamp)?
+        else {
+            continue;
+        };
 
-    let cpi_ctx = CpiContext::new(ctx.accounts.token_program.to_account_info(), cpi_accounts);
-    token::transfer(cpi_ctx, token_amount)?;
+        let option_weight = proposal_account.add_vote(choice, tokens, weight)?;
+        weight_cast = weight_cast.saturating_add(weight);
+        proposal_account.escrowed_tokens = proposal_account
+            .escrowed_tokens
+            .checked_add(tokens)
+            .ok_or(VoteError::ProposalWeightOverflow)?;
 
-    let voter_account = &mut ctx.accounts.voter_account;
-    voter_account.proposal_voted = proposal_id;
+        emit!(VoteCast {
+            voter: delegator,
+            cast_by: authority,
+            proposal_id,
+            choice,
+            tokens,
+            weight,
+            option_weight,
+            total_votes: proposal_account.number_of_votes,
+            total_weight: proposal_account.total_weight,
+            timestamp: clock.unix_timestamp,
+        });
+    }
 
-    proposal_account.number_of_votes = proposal_account
-        .number_of_votes
-        .checked_add(1)
-        .ok_or(VoteError::ProposalVotesOverflow)?;
+    extend_deadline_if_sniped(
+        proposal_account,
+        leader_before,
+        total_weight_before,
+        weight_cast,
+        authority,
+        clock.unix_timestamp,
+    )?;
+
+    Ok(())
+}
+
+/// Applies the proposal's anti-sniping policy after a vote moved `weight_moved` of
+/// weight. Proposals in a voting round keep their deadline, since the round closes on
+/// its own schedule.
+fn extend_deadline_if_sniped(
+    proposal: &mut Proposal,
+    leader_before: Option<u8>,
+    total_weight_before: u64,
+    weight_moved: u64,
+    triggered_by: Pubkey,
+    now: i64,
+) -> Result<()> {
+    let policy = proposal.anti_sniping;
+    if proposal.round_id.is_some()
+        || proposal.extensions >= policy.max_extensions
+        || proposal.deadline.saturating_sub(now) > policy.window
+    {
+        return Ok(());
+    }
+
+    let flipped = proposal.leading_option() != leader_before;
+    let moved = u128::from(weight_moved) * u128::from(BPS_DENOMINATOR)
+        > u128::from(total_weight_before) * u128::from(policy.threshold_bps);
+    if !flipped && !moved {
+        return Ok(());
+    }
+
+    let previous_deadline = proposal.deadline;
+    proposal.deadline = previous_deadline
+        .checked_add(policy.extension)
+        .ok_or(VoteError::InvalidDeadline)?;
+    proposal.extensions += 1;
 
-    emit!(VoteCast {
+    emit!(DeadlineExtended {
+        proposal_id: proposal.proposal_id,
+        triggered_by,
+        previous_deadline,
+        new_deadline: proposal.deadline,
+        extensions: proposal.extensions,
+        timestamp: now,
+    });
+
+    Ok(())
+}
+
+struct DelegatedVoteAccounts<'info> {
+    realm: Pubkey,
+    x_mint: Pubkey,
+    voting_mode: VotingMode,
+    delegate: AccountInfo<'info>,
+    proposal_vault: AccountInfo<'info>,
+    token_program: AccountInfo<'info>,
+    system_program: AccountInfo<'info>,
+}
+
+impl<'info> DelegatedVoteAccounts<'info> {
+    /// Escrows the tokens a delegator approved to its delegation and creates its vote
+    /// record. Returns the delegator with its escrowed tokens and effective votes, or
+    /// `None` when the delegator has already voted or has nothing to vote with.
+    fn escrow(
+        &self,
+        accounts: &'info [AccountInfo<'info>],
+        proposal_id: u64,
+        choice: u8,
+        now: i64,
+    ) -> Result<Option<(Pubkey, u64, u64)>> {
+        let realm_key = self.realm;
+        let delegate = self.delegate.key();
+        let (delegation_info, vote_record_info, token_account_info) =
+            (&accounts[0], &accounts[1], &accounts[2]);
+
+        let delegation = Account::<Delegation>::try_from(delegation_info)?;
+        let delegator = delegation.delegator;
+        let (expected_delegation, _) = Pubkey::find_program_address(
+            &[b"delegate", realm_key.as_ref(), delegator.as_ref()],
+            &crate::ID,
+        );
+        require_keys_eq!(
+            delegation_info.key(),
+            expected_delegation,
+            VoteError::InvalidDelegation
+        );
+        require_keys_eq!(delegation.delegate, delegate, VoteError::InvalidDelegation);
+
+        let proposal_id_bytes = proposal_id.to_be_bytes();
+        let (expected_record, record_bump) = Pubkey::find_program_address(
+            &[
+                b"vote",
+                realm_key.as_ref(),
+                proposal_id_bytes.as_ref(),
+                delegator.as_ref(),
+            ],
+            &crate::ID,
+        );
+        require_keys_eq!(
+            vote_record_info.key(),
+            expected_record,
+            VoteError::InvalidVoteRecord
+        );
+        if !vote_record_info.data_is_empty() {
+            return Ok(None);
+        }
+
+        let token_account = Account::<TokenAccount>::try_from(token_account_info)?;
+        require_keys_eq!(
+            token_account.owner,
+            delegator,
+            VoteError::InvalidTokenAccountOwner
+        );
+        require_keys_eq!(
+            token_account.mint,
+            self.x_mint,
+            VoteError::TokenMintMismatch
+        );
+        if token_account.delegate != COption::Some(delegation_info.key()) {
+            return Ok(None);
+        }
+        let tokens = token_account.delegated_amount.min(token_account.amount);
+        if tokens == 0 {
+            return Ok(None);
+        }
+
+        let delegation_seeds = &[
+            b"delegate".as_ref(),
+            realm_key.as_ref(),
+            delegator.as_ref(),
+            &[delegation.bump],
+        ];
+        token::transfer(
+            CpiContext::new_with_signer(
+                self.token_program.clone(),
+                TokenTransfer {
+                    from: token_account_info.clone(),
+                    to: self.proposal_vault.clone(),
+                    authority: delegation_info.clone(),
+                },
+                &[&delegation_seeds[..]],
+            ),
+            tokens,
+        )?;
+
+        let space = 8 + VoteRecord::INIT_SPACE;
+        let record_seeds = &[
+            b"vote".as_ref(),
+            realm_key.as_ref(),
+            proposal_id_bytes.as_ref(),
+            delegator.as_ref(),
+            &[record_bump],
+        ];
+        self.create_vote_record(vote_record_info, &record_seeds[..], space)?;
+
+        let weight = self.voting_mode.effective_votes(tokens);
+        let vote_record = VoteRecord {
+            proposal_id,
+            voter: delegator,
+            choice,
+            weight,
+            tokens,
+            tokens_locked: tokens,
+            delegated_tokens: tokens,
+            voted_at: now,
+            cast_by: delegate,
+            rent_payer: delegate,
+        };
+        vote_record.try_serialize(&mut &mut vote_record_info.try_borrow_mut_data()?[..])?;
+
+        Ok(Some((delegator, tokens, weight)))
+    }
+
+    /// Creates a delegator's vote record paid by the delegate. Like Anchor's `init`, an
+    /// address that already holds lamports is topped up, allocated and assigned instead,
+    /// so funding the PDA beforehand cannot block the delegate's batch.
+    fn create_vote_record(
+        &self,
+        vote_record_info: &AccountInfo<'info>,
+        record_seeds: &[&[u8]],
+        space: usize,
+    ) -> Result<()> {
+        let rent_exempt = Rent::get()?.minimum_balance(space);
+        let current_lamports = vote_record_info.lamports();
+        if current_lamports == 0 {
+            return system_program::create_account(
+                CpiContext::new_with_signer(
+                    self.system_program.clone(),
+                    system_program::CreateAccount {
+                        from: self.delegate.clone(),
+                        to: vote_record_info.clone(),
+                    },
+                    &[record_seeds],
+                ),
+                rent_exempt,
+                space as u64,
+                &crate::ID,
+            );
+        }
+
+        let shortfall = rent_exempt.saturating_sub(current_lamports);
+        if shortfall > 0 {
+            system_program::transfer(
+                CpiContext::new(
+                    self.system_program.clone(),
+                    system_program::Transfer {
+                        from: self.delegate.clone(),
+                        to: vote_record_info.clone(),
+                    },
+                ),
+                shortfall,
+            )?;
+        }
+        system_program::allocate(
+            CpiContext::new_with_signer(
+                self.system_program.clone(),
+                system_program::Allocate {
+                    account_to_allocate: vote_record_info.clone(),
+                },
+                &[record_seeds],
+            ),
+            space as u64,
+        )?;
+        system_program::assign(
+            CpiContext::new_with_signer(
+                self.system_program.clone(),
+                system_program::Assign {
+                    account_to_assign: vote_record_info.clone(),
+                },
+                &[record_seeds],
+            ),
+            &crate::ID,
+        )
+    }
+}
+
+/// Moves the signer's vote to another option of the same proposal while voting is
+/// open. A vote cast by a delegate becomes the signer's own once it is changed, and
+/// then counts toward the signer's `votes_cast`.
+pub fn change_vote(ctx: Context<ChangeVote>, proposal_id: u64, new_choice: u8) -> Result<()> {
+    let clock = Clock::get()?;
+    let proposal = &mut ctx.accounts.proposal_account;
+    let vote_record = &mut ctx.accounts.vote_record;
+
+    require_voting_open(proposal, clock.unix_timestamp)?;
+    require!(
+        usize::from(new_choice) < proposal.options.len(),
+        VoteError::InvalidOptionIndex
+    );
+    require!(vote_record.choice != new_choice, VoteError::VoteUnchanged);
+
+    let previous_choice = vote_record.choice;
+    let weight = vote_record.weight;
+    let leader_before = proposal.leading_option();
+    let total_weight_before = proposal.total_weight;
+    proposal.remove_vote(previous_choice, vote_record.tokens, weight)?;
+    let new_option_weight = proposal.add_vote(new_choice, vote_record.tokens, weight)?;
+
+    if vote_record.cast_by != ctx.accounts.authority.key() {
+        let voter_account = &mut ctx.accounts.voter_account;
+        voter_account.votes_cast = voter_account
+            .votes_cast
+            .checked_add(1)
+            .ok_or(VoteError::ProposalVotesOverflow)?;
+    }
+    vote_record.choice = new_choice;
+    vote_record.voted_at = clock.unix_timestamp;
+    vote_record.cast_by = ctx.accounts.authority.key();
+
+    emit!(VoteChanged {
         voter: ctx.accounts.authority.key(),
         proposal_id,
-        total_votes: proposal_account.number_of_votes,
+        previous_choice,
+        new_choice,
+        weight,
+        previous_option_weight: proposal.options[usize::from(previous_choice)].weight,
+        new_option_weight,
         timestamp: clock.unix_timestamp,
     });
 
+    extend_deadline_if_sniped(
+        proposal,
+        leader_before,
+        total_weight_before,
+        weight,
+        ctx.accounts.authority.key(),
+        clock.unix_timestamp,
+    )?;
+
     Ok(())
 }
 
-pub fn pick_winner(ctx: Context<PickWinner>, proposal_id: u8) -> Result<()> {
+/// Withdraws the signer's vote while voting is open, refunds its escrowed tokens and
+/// closes its vote record so the signer can vote again later. The record's rent goes back
+/// to the wallet that paid for it.
+pub fn retract_vote(ctx: Context<RetractVote>, proposal_id: u64) -> Result<()> {
     let clock = Clock::get()?;
-    let proposal = &ctx.accounts.proposal_account;
-    let winner = &mut ctx.accounts.winner_account;
+    let choice = ctx.accounts.vote_record.choice;
+    let weight = ctx.accounts.vote_record.weight;
+    let tokens = ctx.accounts.vote_record.tokens;
+    let amount = ctx.accounts.vote_record.tokens_locked;
+    let delegated_tokens = ctx.accounts.vote_record.delegated_tokens;
+
+    require_voting_open(&ctx.accounts.proposal_account, clock.unix_timestamp)?;
+
+    if amount > 0 {
+        let realm_key = ctx.accounts.realm.key();
+        let escrow_authority_seeds = &[
+            b"escrow_authority".as_ref(),
+            realm_key.as_ref(),
+            &[ctx.bumps.escrow_authority],
+        ];
+        let signer_seeds = &[&escrow_authority_seeds[..]];
+
+        let cpi_accounts = TokenTransfer {
+            from: ctx.accounts.proposal_vault.to_account_info(),
+            to: ctx.accounts.voter_token_account.to_account_info(),
+            authority: ctx.accounts.escrow_authority.to_account_info(),
+        };
+        token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                cpi_accounts,
+                signer_seeds,
+            ),
+            amount,
+        )?;
+        restore_delegated_allowance(
+            ctx.accounts.delegation.as_ref(),
+            &ctx.accounts.voter_token_account,
+            &ctx.accounts.authority,
+            &ctx.accounts.token_program,
+            delegated_tokens,
+        )?;
+    }
 
+    let proposal = &mut ctx.accounts.proposal_account;
+    let leader_before = proposal.leading_option();
+    let total_weight_before = proposal.total_weight;
+    proposal.remove_vote(choice, tokens, weight)?;
+    proposal.escrowed_tokens = proposal
+        .escrowed_tokens
+        .checked_sub(amount)
+        .ok_or(VoteError::EscrowNotEmpty)?;
+
+    // A vote a delegate cast for the signer never counted toward its `votes_cast`.
+    if ctx.accounts.vote_record.cast_by == ctx.accounts.authority.key() {
+        let voter_account = &mut ctx.accounts.voter_account;
+        voter_account.votes_cast = voter_account
+            .votes_cast
+            .checked_sub(1)
+            .ok_or(VoteError::ProposalVotesOverflow)?;
+    }
+
+    emit!(VoteRetracted {
+        voter: ctx.accounts.authority.key(),
+        proposal_id,
+        choice,
+        weight,
+        tokens_refunded: amount,
+        total_votes: proposal.number_of_votes,
+        total_weight: proposal.total_weight,
+        timestamp: clock.unix_timestamp,
+    });
+
+    extend_deadline_if_sniped(
+        proposal,
+        leader_before,
+        total_weight_before,
+        weight,
+        ctx.accounts.authority.key(),
+        clock.unix_timestamp,
+    )?;
+
+    Ok(())
+}
+
+pub(crate) fn require_voting_open(proposal: &Proposal, now: i64) -> Result<()> {
+    require!(
+        proposal.state == ProposalState::Active,
+        VoteError::ProposalNotActive
+    );
+    require!(
+        now >= proposal.voting_starts_at,
+        VoteError::VotingNotStarted
+    );
+    require!(proposal.deadline > now, VoteError::ProposalEnded);
+    Ok(())
+}
+
+pub fn pick_winner(ctx: Context<PickWinner>, proposal_id: u64) -> Result<()> {
+    let clock = Clock::get()?;
+    let proposal = &mut ctx.accounts.proposal_account;
+
+    match proposal.state {
+        ProposalState::Active => {}
+        ProposalState::Draft | ProposalState::Cancelled => {
+            return err!(VoteError::ProposalNotActive)
+        }
+        _ => return err!(VoteError::ProposalAlreadyResolved),
+    }
     require!(
-        clock.unix_timestamp >= proposal.deadline,
+        clock.unix_timestamp >= proposal.reveal_ends_at.unwrap_or(proposal.deadline),
         VoteError::VotingStillActive
     );
-    require!(proposal.number_of_votes > 0, VoteError::NoVotesCast);
+    let lost_round = match proposal.round_id {
+        Some(round_id) => {
+            let voting_round = ctx
+                .accounts
+                .voting_round
+                .as_ref()
+                .filter(|voting_round| {
+                    voting_round.round_id == round_id && voting_round.finalized_at.is_some()
+                })
+                .ok_or(VoteError::RoundNotFinalized)?;
+            if voting_round.awaits_runoff(proposal_id) {
+                // A proposal that tied under `Runoff` is decided by the runoff round.
+                let runoff_round_id = voting_round
+                    .runoff_round_id
+                    .ok_or(VoteError::RunoffPending)?;
+                let runoff_round = ctx
+                    .accounts
+                    .runoff_round
+                    .as_ref()
+                    .filter(|runoff_round| {
+                        runoff_round.round_id == runoff_round_id
+                            && runoff_round.finalized_at.is_some()
+                    })
+                    .ok_or(VoteError::RunoffPending)?;
+                runoff_round.winning_proposal_id != Some(proposal_id)
+            } else {
+                voting_round.winning_proposal_id != Some(proposal_id)
+            }
+        }
+        None => false,
+    };
+
+    let quorum_reached = proposal.number_of_votes > 0
+        && proposal.number_of_votes >= proposal.quorum_votes
+        && proposal.total_tokens >= proposal.quorum_tokens;
+    if lost_round {
+        defeat_proposal(proposal, DefeatReason::LostRound, clock.unix_timestamp)?;
+    } else if quorum_reached {
+        let mut winning_index = 0;
+        for (index, option) in proposal.options.iter().enumerate() {
+            if option.weight > proposal.options[winning_index].weight {
+                winning_index = index;
+            }
+        }
+
+        let top_weight = proposal.options[winning_index].weight;
+        let tied: Vec<u64> = proposal
+            .options
+            .iter()
+            .enumerate()
+            .filter(|(_, option)| option.weight == top_weight)
+            .map(|(index, _)| index as u64)
+            .collect();
+        // The tie policy only breaks ties between round proposals. A tie between the
+        // options of one proposal always defeats it, so a split vote never approves it.
+        let tie = tied.len() > 1;
+        if tie {
+            emit!(TieDetected {
+                proposal_id: Some(proposal_id),
+                round_id: None,
+                tied,
+                weight: top_weight,
+                policy: proposal.tie_policy,
+                winner: None,
+                timestamp: clock.unix_timestamp,
+            });
+        }
+
+        let approved = u128::from(top_weight) * u128::from(BPS_DENOMINATOR)
+            >= u128::from(proposal.total_weight) * u128::from(proposal.approval_threshold_bps);
+        if tie {
+            defeat_proposal(proposal, DefeatReason::Tie, clock.unix_timestamp)?;
+        } else if approved {
+            let winning_option =
+                u8::try_from(winning_index).map_err(|_| VoteError::InvalidOptionIndex)?;
+            proposal.winning_option = Some(winning_option);
+            proposal.transition_to(ProposalState::Succeeded)?;
+
+            let option = &proposal.options[winning_index];
+            emit!(WinnerDeclared {
+                proposal_id,
+                winning_option,
+                option_label: option.label.clone(),
+                option_votes: option.votes,
+                option_weight: option.weight,
+                total_votes: proposal.number_of_votes,
+                total_weight: proposal.total_weight,
+                declared_by: ctx.accounts.authority.key(),
+                timestamp: clock.unix_timestamp,
+            });
+        } else {
+            defeat_proposal(
+                proposal,
+                DefeatReason::ApprovalThresholdNotMet,
+                clock.unix_timestamp,
+            )?;
+        }
+    } else {
+        defeat_proposal(
+            proposal,
+            DefeatReason::QuorumNotReached,
+            clock.unix_timestamp,
+        )?;
+    }
+
+    let proposal_result = &mut ctx.accounts.proposal_result;
+    proposal_result.proposal_id = proposal_id;
+    proposal_result.outcome = proposal.state;
+    proposal_result.winning_option = proposal.winning_option;
+    proposal_result.defeat_reason = proposal.defeat_reason;
+    proposal_result.options = proposal.options.clone();
+    proposal_result.total_votes = proposal.number_of_votes;
+    proposal_result.total_weight = proposal.total_weight;
+    proposal_result.resolved_by = ctx.accounts.authority.key();
+    proposal_result.resolved_at = clock.unix_timestamp;
+    proposal_result.unrevealed_commitments = proposal.unrevealed_commitments;
+
+    if proposal.unrevealed_commitments > 0 {
+        emit!(UnrevealedCommitments {
+            proposal_id,
+            count: proposal.unrevealed_commitments,
+            bond_per_commitment: proposal.commit_bond,
+            timestamp: clock.unix_timestamp,
+        });
+    }
+
+    Ok(())
+}
+
+/// Executes the next pending instruction of a succeeded proposal, signed by the
+/// governance PDA. The accounts of that instruction, including its program, are passed
+/// in `remaining_accounts`. Once every instruction has run the proposal becomes executed.
+pub fn execute_proposal<'info>(
+    ctx: Context<'_, '_, 'info, 'info, ExecuteProposal<'info>>,
+    proposal_id: u64,
+) -> Result<()> {
+    let clock = Clock::get()?;
+    let proposal = &mut ctx.accounts.proposal_account;
+
+    require!(
+        proposal.state == ProposalState::Succeeded,
+        VoteError::InvalidStateTransition
+    );
+    require!(proposal.is_approved(), VoteError::ProposalNotApproved);
+
+    if proposal.instructions_executed < proposal.instruction_count {
+        let proposal_instruction = ctx
+            .accounts
+            .proposal_instruction
+            .as_mut()
+            .ok_or(VoteError::MissingProposalInstruction)?;
+
+        let instruction = Instruction {
+            program_id: proposal_instruction.program_id,
+            accounts: proposal_instruction
+                .accounts
+                .iter()
+                .map(|account| AccountMeta {
+                    pubkey: account.pubkey,
+                    is_signer: account.is_signer,
+                    is_writable: account.is_writable,
+                })
+                .collect(),
+            data: proposal_instruction.data.clone(),
+        };
+
+        let mut account_infos = ctx.remaining_accounts.to_vec();
+        account_infos.push(ctx.accounts.governance_authority.to_account_info());
+
+        let realm_key = ctx.accounts.realm.key();
+        let governance_seeds = &[
+            b"governance".as_ref(),
+            realm_key.as_ref(),
+            &[ctx.bumps.governance_authority],
+        ];
+        invoke_signed(&instruction, &account_infos, &[&governance_seeds[..]])?;
+
+        proposal_instruction.executed_at = Some(clock.unix_timestamp);
+        proposal.instructions_executed += 1;
+
+        emit!(ProposalInstructionExecuted {
+            proposal_id,
+            index: proposal_instruction.index,
+            program_id: proposal_instruction.program_id,
+            executed_by: ctx.accounts.authority.key(),
+            timestamp: clock.unix_timestamp,
+        });
+    }
 
-    if proposal.number_of_votes > winner.winning_votes {
-        winner.winning_proposal_id = proposal_id;
-        winner.winning_votes = proposal.number_of_votes;
-        winner.proposal_info = proposal.proposal_info.clone();
-        winner.declared_at = clock.unix_timestamp;
+    if proposal.instructions_executed == proposal.instruction_count {
+        proposal.transition_to(ProposalState::Executed)?;
 
-        emit!(WinnerDeclared {
-            winning_proposal_id: proposal_id,
-            proposal_info: proposal.proposal_info.clone(),
-            total_votes: proposal.number_of_votes,
-            declared_by: ctx.accounts.authority.key(),
+        emit!(ProposalExecuted {
+            proposal_id,
+            executed_by: ctx.accounts.authority.key(),
             timestamp: clock.unix_timestamp,
         });
     }
@@ -142,30 +1215,249 @@ pub fn pick_winner(ctx: Context<PickWinner>, proposal_id: u8) -> Result<()> {
     Ok(())
 }
 
-pub fn close_proposal(ctx: Context<CloseProposal>, proposal_id: u8) -> Result<()> {
+/// Closes the proposal and its escrow vault once it is defeated, executed, cancelled or
+/// won by an option other than its approving one, and every escrowed token has been
+/// released. Tokens sent to the vault outside of
+/// voting are swept into the treasury. Vote records of the proposal can be
+/// passed as `(vote_record, rent_payer)` pairs in `remaining_accounts`; each one is
+/// closed and its rent returned to the wallet that paid for it, which is the delegate
+/// for records created through delegation.
+pub fn close_proposal<'info>(
+    ctx: Context<'_, '_, 'info, 'info, CloseProposal<'info>>,
+    proposal_id: u64,
+) -> Result<()> {
     let clock = Clock::get()?;
     let proposal = &ctx.accounts.proposal_account;
 
     require!(
-        clock.unix_timestamp >= proposal.deadline,
+        proposal.state != ProposalState::Active,
         VoteError::VotingStillActive
     );
+    require!(
+        proposal.state.is_closable()
+            || (proposal.state == ProposalState::Succeeded && !proposal.is_approved()),
+        VoteError::InvalidStateTransition
+    );
+    require!(proposal.escrowed_tokens == 0, VoteError::EscrowNotEmpty);
+    require!(
+        ctx.remaining_accounts.len() % 2 == 0,
+        VoteError::InvalidRemainingAccounts
+    );
+
+    let realm_key = ctx.accounts.realm.key();
+    let escrow_authority_seeds = &[
+        b"escrow_authority".as_ref(),
+        realm_key.as_ref(),
+        &[ctx.bumps.escrow_authority],
+    ];
+    let signer_seeds = &[&escrow_authority_seeds[..]];
+
+    let stray_tokens = ctx.accounts.proposal_vault.amount;
+    if stray_tokens > 0 {
+        let cpi_accounts = TokenTransfer {
+            from: ctx.accounts.proposal_vault.to_account_info(),
+            to: ctx.accounts.treasury_token_account.to_account_info(),
+            authority: ctx.accounts.escrow_authority.to_account_info(),
+        };
+        token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                cpi_accounts,
+                signer_seeds,
+            ),
+            stray_tokens,
+        )?;
+    }
+
+    let cpi_accounts = token::CloseAccount {
+        account: ctx.accounts.proposal_vault.to_account_info(),
+        destination: ctx.accounts.destination.to_account_info(),
+        authority: ctx.accounts.escrow_authority.to_account_info(),
+    };
+    token::close_account(CpiContext::new_with_signer(
+        ctx.accounts.token_program.to_account_info(),
+        cpi_accounts,
+        signer_seeds,
+    ))?;
+
+    let mut vote_records_closed: u64 = 0;
+    for pair in ctx.remaining_accounts.chunks(2) {
+        let vote_record = Account::<VoteRecord>::try_from(&pair[0])?;
+        let rent_payer = &pair[1];
+        require!(
+            vote_record.proposal_id == proposal_id,
+            VoteError::InvalidVoteRecord
+        );
+        require_keys_eq!(
+            vote_record.rent_payer,
+            rent_payer.key(),
+            VoteError::InvalidVoteRecord
+        );
+        require_keys_eq!(
+            vote_record.key(),
+            vote_record_address(&realm_key, proposal_id, &vote_record.voter),
+            VoteError::InvalidVoteRecord
+        );
+
+        vote_record.close(rent_payer.clone())?;
+        vote_records_closed += 1;
+    }
 
     emit!(ProposalClosed {
         proposal_id,
         rent_recovered: ctx.accounts.proposal_account.to_account_info().lamports(),
         recovered_to: ctx.accounts.authority.key(),
+        vote_records_closed,
         timestamp: clock.unix_timestamp,
     });
 
     Ok(())
 }
 
-pub fn close_voter(ctx: Context<CloseVoter>) -> Result<()> {
+/// Closes the voter profile. The voter's own vote records can be passed as
+/// `(vote_record, proposal)` pairs in `remaining_accounts`; a record is only closed once
+/// its proposal no longer accepts votes or has already been closed, so it cannot be used
+/// to vote twice. Records whose rent a delegate paid are closed with `close_proposal`.
+pub fn close_voter<'info>(ctx: Context<'_, '_, 'info, 'info, CloseVoter<'info>>) -> Result<()> {
+    let clock = Clock::get()?;
+    let authority = ctx.accounts.authority.key();
+    let realm_key = ctx.accounts.realm.key();
+
+    require!(
+        ctx.remaining_accounts.len() % 2 == 0,
+        VoteError::InvalidRemainingAccounts
+    );
+
+    let mut vote_records_closed: u64 = 0;
+    for pair in ctx.remaining_accounts.chunks(2) {
+        let vote_record = Account::<VoteRecord>::try_from(&pair[0])?;
+        require_keys_eq!(vote_record.voter, authority, VoteError::InvalidVoteRecord);
+        require_keys_eq!(
+            vote_record.rent_payer,
+            authority,
+            VoteError::InvalidVoteRecord
+        );
+        require_keys_eq!(
+            vote_record.key(),
+            vote_record_address(&realm_key, vote_record.proposal_id, &authority),
+            VoteError::InvalidVoteRecord
+        );
+        require!(
+            vote_record.tokens_locked == 0,
+            VoteError::VoteTokensStillLocked
+        );
+        require!(
+            proposal_has_ended(
+                &pair[1],
+                &realm_key,
+                vote_record.proposal_id,
+                clock.unix_timestamp
+            )?,
+            VoteError::VotingStillActive
+        );
+
+        vote_record.close(ctx.accounts.authority.to_account_info())?;
+        vote_records_closed += 1;
+    }
+
     emit!(VoterAccountClosed {
         voter: ctx.accounts.voter_account.voter_id,
-        rent_recovered_to: ctx.accounts.authority.key(),
-        timestamp: Clock::get()?.unix_timestamp,
+        rent_recovered_to: authority,
+        vote_records_closed,
+        timestamp: clock.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+fn vote_record_address(realm: &Pubkey, proposal_id: u64, voter: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[
+            b"vote",
+            realm.as_ref(),
+            proposal_id.to_be_bytes().as_ref(),
+            voter.as_ref(),
+        ],
+        &crate::ID,
+    )
+    .0
+}
+
+fn proposal_has_ended<'info>(
+    proposal_info: &'info AccountInfo<'info>,
+    realm: &Pubkey,
+    proposal_id: u64,
+    now: i64,
+) -> Result<bool> {
+    let (expected_key, _) = Pubkey::find_program_address(
+        &[
+            b"proposal",
+            realm.as_ref(),
+            proposal_id.to_be_bytes().as_ref(),
+        ],
+        &crate::ID,
+    );
+    require_keys_eq!(
+        proposal_info.key(),
+        expected_key,
+        VoteError::InvalidVoteRecord
+    );
+
+    if proposal_info.data_is_empty() {
+        return Ok(true);
+    }
+
+    let proposal = Account::<Proposal>::try_from(proposal_info)?;
+    Ok(!proposal.accepts_votes(now))
+}
+
+fn validate_governance_params(params: &GovernanceParams) -> Result<()> {
+    require!(
+        u64::from(params.quorum_supply_bps) <= BPS_DENOMINATOR
+            && u64::from(params.approval_threshold_bps) <= BPS_DENOMINATOR
+            && u64::from(params.conviction_weight_bps) <= BPS_DENOMINATOR
+            && u64::from(params.conviction_max_ratio_bps) <= BPS_DENOMINATOR
+            && params.conviction_half_life >= 0
+            && params.voting_delay >= 0,
+        VoteError::InvalidGovernanceConfig
+    );
+    require!(
+        params.min_voting_period > 0 && params.min_voting_period <= params.max_voting_period,
+        VoteError::InvalidVotingPeriodBounds
+    );
+    require!(
+        !params.commit_reveal || (params.reveal_period > 0 && !params.snapshot_voting),
+        VoteError::InvalidGovernanceConfig
+    );
+    require!(
+        params.conviction_half_life == 0
+            || (params.conviction_weight_bps > 0 && params.conviction_max_ratio_bps > 0),
+        VoteError::InvalidGovernanceConfig
+    );
+    let anti_sniping = &params.anti_sniping;
+    require!(
+        anti_sniping.max_extensions == 0
+            || (anti_sniping.window > 0
+                && anti_sniping.extension > 0
+                && u64::from(anti_sniping.threshold_bps) <= BPS_DENOMINATOR),
+        VoteError::InvalidGovernanceConfig
+    );
+    Ok(())
+}
+
+fn defeat_proposal(proposal: &mut Proposal, reason: DefeatReason, now: i64) -> Result<()> {
+    proposal.transition_to(ProposalState::Defeated)?;
+    proposal.defeat_reason = Some(reason);
+
+    emit!(ProposalDefeated {
+        proposal_id: proposal.proposal_id,
+        reason,
+        total_votes: proposal.number_of_votes,
+        total_weight: proposal.total_weight,
+        quorum_votes: proposal.quorum_votes,
+        quorum_tokens: proposal.quorum_tokens,
+        approval_threshold_bps: proposal.approval_threshold_bps,
+        timestamp: now,
     });
 
     Ok(())
@@ -0,0 +1,300 @@
+use anchor_lang::prelude::*;
+
+use crate::contexts::{StartRankedTally, SubmitRankedBallot, TallyRankedBallots};
+use crate::errors::VoteError;
+use crate::events::*;
+use crate::instructions::round::{require_round_ended, round_candidates};
+use crate::state::{RankedBallot, RankedTally, TiePolicy, VotingRound};
+
+/// Records the signer's ordered ranking of proposals in a ranked-choice round. The
+/// ballot weighs the power the signer's stake held before the round started, measured
+/// at the round's end.
+pub fn submit_ranked_ballot(
+    ctx: Context<SubmitRankedBallot>,
+    round_id: u64,
+    rankings: Vec<u64>,
+) -> Result<()> {
+    let clock = Clock::get()?;
+    let voting_round = &mut ctx.accounts.voting_round;
+
+    require!(voting_round.ranked_choice, VoteError::NotRankedChoiceRound);
+    require!(
+        voting_round.is_open(clock.unix_timestamp),
+        VoteError::VotingRoundClosed
+    );
+    require!(!rankings.is_empty(), VoteError::InvalidRanking);
+    for (index, proposal_id) in rankings.iter().enumerate() {
+        require!(
+            voting_round.proposal_ids.contains(proposal_id)
+                && !rankings[..index].contains(proposal_id),
+            VoteError::InvalidRanking
+        );
+    }
+
+    let checkpoint = ctx
+        .accounts
+        .stake_position
+        .checkpoint_before(voting_round.starts_at)
+        .ok_or(VoteError::NoStakeAtSnapshot)?;
+    let weight = checkpoint.voting_power_at(voting_round.ends_at);
+    require!(weight > 0, VoteError::StakeExpiresBeforeDeadline);
+
+    let ballot = &mut ctx.accounts.ballot;
+    ballot.round_id = round_id;
+    ballot.voter = ctx.accounts.authority.key();
+    ballot.rankings = rankings.clone();
+    ballot.weight = weight;
+    ballot.last_counted_pass = 0;
+    ballot.submitted_at = clock.unix_timestamp;
+
+    voting_round.ballot_count = voting_round
+        .ballot_count
+        .checked_add(1)
+        .ok_or(VoteError::ProposalVotesOverflow)?;
+
+    emit!(RankedBallotSubmitted {
+        round_id,
+        voter: ballot.voter,
+        rankings,
+        weight,
+        timestamp: clock.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+/// Opens the instant-runoff count of an ended ranked-choice round. Every proposal of
+/// the round must be passed in `remaining_accounts`, in the order they joined the
+/// round; draft and cancelled proposals are left out of the count.
+pub fn start_ranked_tally<'info>(
+    ctx: Context<'_, '_, 'info, 'info, StartRankedTally<'info>>,
+    round_id: u64,
+) -> Result<()> {
+    let clock = Clock::get()?;
+    let realm_key = ctx.accounts.realm.key();
+    let voting_round = &mut ctx.accounts.voting_round;
+
+    require!(voting_round.ranked_choice, VoteError::NotRankedChoiceRound);
+    require_round_ended(voting_round, clock.unix_timestamp)?;
+
+    let candidates = round_candidates(
+        ctx.remaining_accounts,
+        &realm_key,
+        voting_round.member_round_id(),
+        &voting_round.proposal_ids,
+        clock.unix_timestamp,
+    )?;
+
+    let ranked_tally = &mut ctx.accounts.ranked_tally;
+    ranked_tally.round_id = round_id;
+    ranked_tally.pass = 1;
+    ranked_tally.candidates = candidates
+        .iter()
+        .map(|(proposal_id, _)| *proposal_id)
+        .collect();
+    ranked_tally.weights = vec![0; candidates.len()];
+    ranked_tally.exhausted_weight = 0;
+    ranked_tally.ballots_counted = 0;
+    ranked_tally.bump = ctx.bumps.ranked_tally;
+
+    complete_pass(
+        voting_round,
+        ranked_tally,
+        ctx.accounts.authority.key(),
+        clock.unix_timestamp,
+    )
+}
+
+/// Crank that counts a batch of ballots, passed in `remaining_accounts`, for the
+/// current pass. Ballots already counted in this pass are skipped. Once every ballot
+/// of the round has been counted the pass is closed: a candidate holding a majority of
+/// the live weight wins the round, otherwise the weakest candidate is eliminated and
+/// a new pass over all ballots begins.
+pub fn tally_ranked_ballots<'info>(
+    ctx: Context<'_, '_, 'info, 'info, TallyRankedBallots<'info>>,
+    round_id: u64,
+) -> Result<()> {
+    let clock = Clock::get()?;
+    let realm_key = ctx.accounts.realm.key();
+    let voting_round = &mut ctx.accounts.voting_round;
+    let ranked_tally = &mut ctx.accounts.ranked_tally;
+
+    require!(
+        voting_round.finalized_at.is_none(),
+        VoteError::VotingRoundAlreadyFinalized
+    );
+
+    for ballot_info in ctx.remaining_accounts {
+        let mut ballot = Account::<RankedBallot>::try_from(ballot_info)?;
+        let (expected_key, _) = Pubkey::find_program_address(
+            &[
+                b"ballot",
+                realm_key.as_ref(),
+                round_id.to_be_bytes().as_ref(),
+                ballot.voter.as_ref(),
+            ],
+            &crate::ID,
+        );
+        require_keys_eq!(ballot_info.key(), expected_key, VoteError::InvalidBallot);
+        if ballot.last_counted_pass >= ranked_tally.pass {
+            continue;
+        }
+
+        let preferred = ballot.rankings.iter().find_map(|proposal_id| {
+            ranked_tally
+                .candidates
+                .iter()
+                .position(|candidate| candidate == proposal_id)
+        });
+        match preferred {
+            Some(index) => {
+                ranked_tally.weights[index] = ranked_tally.weights[index]
+                    .checked_add(ballot.weight)
+                    .ok_or(VoteError::ProposalWeightOverflow)?;
+            }
+            None => {
+                ranked_tally.exhausted_weight = ranked_tally
+                    .exhausted_weight
+                    .checked_add(ballot.weight)
+                    .ok_or(VoteError::ProposalWeightOverflow)?;
+            }
+        }
+        ranked_tally.ballots_counted += 1;
+
+        ballot.last_counted_pass = ranked_tally.pass;
+        ballot.exit(&crate::ID)?;
+    }
+
+    complete_pass(
+        voting_round,
+        ranked_tally,
+        ctx.accounts.authority.key(),
+        clock.unix_timestamp,
+    )
+}
+
+/// Closes the current pass once all ballots are in. When every remaining candidate is
+/// tied the round's tie policy decides: the earliest proposal wins, the tied proposals
+/// are recorded for a runoff round, or no winner is declared. Ties for elimination
+/// remove the latest tied candidate, or all of them under the other policies.
+fn complete_pass(
+    voting_round: &mut VotingRound,
+    ranked_tally: &mut RankedTally,
+    finalized_by: Pubkey,
+    now: i64,
+) -> Result<()> {
+    if ranked_tally.ballots_counted < voting_round.ballot_count {
+        return Ok(());
+    }
+
+    let live_weight = ranked_tally
+        .weights
+        .iter()
+        .try_fold(0u64, |total, weight| total.checked_add(*weight))
+        .ok_or(VoteError::ProposalWeightOverflow)?;
+    let top_weight = ranked_tally
+        .weights
+        .iter()
+        .copied()
+        .max()
+        .unwrap_or_default();
+    let low_weight = ranked_tally
+        .weights
+        .iter()
+        .copied()
+        .min()
+        .unwrap_or_default();
+
+    if live_weight == 0 {
+        return finalize_ranked_round(voting_round, None, finalized_by, now);
+    }
+    if ranked_tally.candidates.len() == 1 || u128::from(top_weight) * 2 > u128::from(live_weight) {
+        let index = ranked_tally
+            .weights
+            .iter()
+            .position(|weight| *weight == top_weight)
+            .unwrap_or_default();
+        let winner = (ranked_tally.candidates[index], top_weight);
+        return finalize_ranked_round(voting_round, Some(winner), finalized_by, now);
+    }
+    if top_weight == low_weight {
+        let tied = ranked_tally.candidates.clone();
+        let winner = match voting_round.tie_policy {
+            TiePolicy::EarliestCreated => tied.first().copied(),
+            TiePolicy::Runoff | TiePolicy::NoWinner => None,
+        };
+        if voting_round.tie_policy == TiePolicy::Runoff {
+            voting_round.tied_proposal_ids = tied.clone();
+        }
+        emit!(TieDetected {
+            proposal_id: None,
+            round_id: Some(voting_round.round_id),
+            tied,
+            weight: top_weight,
+            policy: voting_round.tie_policy,
+            winner,
+            timestamp: now,
+        });
+        return finalize_ranked_round(
+            voting_round,
+            winner.map(|proposal_id| (proposal_id, top_weight)),
+            finalized_by,
+            now,
+        );
+    }
+
+    let mut weakest: Vec<usize> = ranked_tally
+        .weights
+        .iter()
+        .enumerate()
+        .filter(|(_, weight)| **weight == low_weight)
+        .map(|(index, _)| index)
+        .collect();
+    if voting_round.tie_policy == TiePolicy::EarliestCreated {
+        weakest.drain(..weakest.len() - 1);
+    }
+    for index in weakest.into_iter().rev() {
+        let proposal_id = ranked_tally.candidates.remove(index);
+        ranked_tally.weights.remove(index);
+
+        emit!(CandidateEliminated {
+            round_id: voting_round.round_id,
+            pass: ranked_tally.pass,
+            proposal_id,
+            weight: low_weight,
+            exhausted_weight: ranked_tally.exhausted_weight,
+            timestamp: now,
+        });
+    }
+
+    ranked_tally
+        .weights
+        .iter_mut()
+        .for_each(|weight| *weight = 0);
+    ranked_tally.exhausted_weight = 0;
+    ranked_tally.ballots_counted = 0;
+    ranked_tally.pass += 1;
+
+    Ok(())
+}
+
+fn finalize_ranked_round(
+    voting_round: &mut VotingRound,
+    winner: Option<(u64, u64)>,
+    finalized_by: Pubkey,
+    now: i64,
+) -> Result<()> {
+    voting_round.winning_proposal_id = winner.map(|(proposal_id, _)| proposal_id);
+    voting_round.winning_weight = winner.map(|(_, weight)| weight).unwrap_or_default();
+    voting_round.finalized_at = Some(now);
+
+    emit!(RoundFinalized {
+        round_id: voting_round.round_id,
+        winning_proposal_id: voting_round.winning_proposal_id,
+        winning_weight: voting_round.winning_weight,
+        finalized_by,
+        timestamp: now,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,27 @@
+use anchor_lang::prelude::*;
+
+use crate::contexts::CreateRealm;
+use crate::errors::VoteError;
+use crate::events::*;
+use crate::state::MAX_REALM_NAME_LEN;
+
+pub fn create_realm(ctx: Context<CreateRealm>, name: String) -> Result<()> {
+    require!(
+        !name.is_empty() && name.len() <= MAX_REALM_NAME_LEN,
+        VoteError::InvalidRealmName
+    );
+
+    let realm = &mut ctx.accounts.realm;
+    realm.authority = ctx.accounts.authority.key();
+    realm.name = name.clone();
+    realm.bump = ctx.bumps.realm;
+
+    emit!(RealmCreated {
+        realm: realm.key(),
+        authority: realm.authority,
+        name,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,241 @@
+use anchor_lang::prelude::*;
+
+use crate::contexts::{CreateRunoffRound, CreateVotingRound, FinalizeRound};
+use crate::errors::VoteError;
+use crate::events::*;
+use crate::state::{Proposal, ProposalState, TiePolicy, VotingRound};
+
+pub fn create_voting_round(
+    ctx: Context<CreateVotingRound>,
+    starts_at: i64,
+    ends_at: i64,
+    ranked_choice: bool,
+) -> Result<()> {
+    let clock = Clock::get()?;
+    require!(
+        starts_at < ends_at && ends_at > clock.unix_timestamp,
+        VoteError::InvalidVotingRound
+    );
+
+    let proposal_counter_account = &mut ctx.accounts.proposal_counter_account;
+    let voting_round = &mut ctx.accounts.voting_round;
+    voting_round.round_id = proposal_counter_account.round_count;
+    voting_round.authority = ctx.accounts.authority.key();
+    voting_round.starts_at = starts_at;
+    voting_round.ends_at = ends_at;
+    voting_round.proposal_ids = Vec::new();
+    voting_round.winning_proposal_id = None;
+    voting_round.winning_weight = 0;
+    voting_round.finalized_at = None;
+    voting_round.tie_policy = ctx.accounts.governance_config_account.params.tie_policy;
+    voting_round.tied_proposal_ids = Vec::new();
+    voting_round.ranked_choice = ranked_choice;
+    voting_round.ballot_count = 0;
+    voting_round.runoff_of = None;
+    voting_round.runoff_round_id = None;
+
+    proposal_counter_account.round_count = proposal_counter_account
+        .round_count
+        .checked_add(1)
+        .ok_or(VoteError::ProposalCounterOverflow)?;
+
+    emit!(VotingRoundCreated {
+        round_id: voting_round.round_id,
+        authority: voting_round.authority,
+        starts_at,
+        ends_at,
+        ranked_choice,
+        timestamp: clock.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+/// Decides the round once it has ended. Anyone can call it, but every proposal of the
+/// round must be passed in `remaining_accounts`, in the order they joined the round.
+/// The proposal with the most weight on its approving option wins; ties are settled by
+/// the round's tie policy. Under `Runoff` no winner is declared and the tied proposals
+/// are recorded for `create_runoff_round`. Draft and cancelled proposals cannot win.
+pub fn finalize_round<'info>(
+    ctx: Context<'_, '_, 'info, 'info, FinalizeRound<'info>>,
+    round_id: u64,
+) -> Result<()> {
+    let clock = Clock::get()?;
+    let realm_key = ctx.accounts.realm.key();
+    let voting_round = &mut ctx.accounts.voting_round;
+
+    require!(!voting_round.ranked_choice, VoteError::RankedChoiceRound);
+    require_round_ended(voting_round, clock.unix_timestamp)?;
+
+    let candidates = round_candidates(
+        ctx.remaining_accounts,
+        &realm_key,
+        voting_round.member_round_id(),
+        &voting_round.proposal_ids,
+        clock.unix_timestamp,
+    )?;
+
+    let top_weight = candidates.iter().map(|(_, weight)| *weight).max();
+    let mut tied: Vec<u64> = candidates
+        .iter()
+        .filter(|(_, weight)| Some(*weight) == top_weight)
+        .map(|(proposal_id, _)| *proposal_id)
+        .collect();
+    tied.sort_unstable();
+
+    let winner = if tied.len() > 1 {
+        let winner = match voting_round.tie_policy {
+            TiePolicy::EarliestCreated => tied.first().copied(),
+            TiePolicy::Runoff | TiePolicy::NoWinner => None,
+        };
+        if voting_round.tie_policy == TiePolicy::Runoff {
+            voting_round.tied_proposal_ids = tied.clone();
+        }
+        emit!(TieDetected {
+            proposal_id: None,
+            round_id: Some(round_id),
+            tied,
+            weight: top_weight.unwrap_or_default(),
+            policy: voting_round.tie_policy,
+            winner,
+            timestamp: clock.unix_timestamp,
+        });
+        winner
+    } else {
+        tied.first().copied()
+    };
+
+    voting_round.winning_proposal_id = winner;
+    voting_round.winning_weight = top_weight.filter(|_| winner.is_some()).unwrap_or_default();
+    voting_round.finalized_at = Some(clock.unix_timestamp);
+
+    emit!(RoundFinalized {
+        round_id,
+        winning_proposal_id: voting_round.winning_proposal_id,
+        winning_weight: voting_round.winning_weight,
+        finalized_by: ctx.accounts.authority.key(),
+        timestamp: clock.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+/// Opens a ranked-choice round between the proposals that tied for a round finalized
+/// under the `Runoff` tie policy. It starts immediately and runs until `ends_at`. The
+/// runoff round breaks its own ties by proposal id, so it always settles the tie.
+pub fn create_runoff_round(
+    ctx: Context<CreateRunoffRound>,
+    round_id: u64,
+    ends_at: i64,
+) -> Result<()> {
+    let clock = Clock::get()?;
+    let tied_round = &mut ctx.accounts.tied_round;
+
+    require!(
+        tied_round.finalized_at.is_some(),
+        VoteError::RoundNotFinalized
+    );
+    require!(
+        tied_round.tie_policy == TiePolicy::Runoff
+            && !tied_round.tied_proposal_ids.is_empty()
+            && tied_round.runoff_round_id.is_none(),
+        VoteError::NoRunoffRequired
+    );
+    require!(
+        ends_at > clock.unix_timestamp,
+        VoteError::InvalidVotingRound
+    );
+
+    let proposal_counter_account = &mut ctx.accounts.proposal_counter_account;
+    let runoff_round = &mut ctx.accounts.runoff_round;
+    runoff_round.round_id = proposal_counter_account.round_count;
+    runoff_round.authority = ctx.accounts.authority.key();
+    runoff_round.starts_at = clock.unix_timestamp;
+    runoff_round.ends_at = ends_at;
+    runoff_round.proposal_ids = tied_round.tied_proposal_ids.clone();
+    runoff_round.winning_proposal_id = None;
+    runoff_round.winning_weight = 0;
+    runoff_round.finalized_at = None;
+    runoff_round.tie_policy = TiePolicy::EarliestCreated;
+    runoff_round.tied_proposal_ids = Vec::new();
+    runoff_round.ranked_choice = true;
+    runoff_round.ballot_count = 0;
+    runoff_round.runoff_of = Some(round_id);
+    runoff_round.runoff_round_id = None;
+
+    tied_round.runoff_round_id = Some(runoff_round.round_id);
+    proposal_counter_account.round_count = proposal_counter_account
+        .round_count
+        .checked_add(1)
+        .ok_or(VoteError::ProposalCounterOverflow)?;
+
+    emit!(RunoffRoundCreated {
+        round_id: runoff_round.round_id,
+        runoff_of: round_id,
+        proposal_ids: runoff_round.proposal_ids.clone(),
+        starts_at: runoff_round.starts_at,
+        ends_at,
+        timestamp: clock.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+/// Verifies that `proposal_infos` are exactly the round's proposals, in order, and
+/// returns the `(proposal_id, approval_weight)` of those that can still win. Draft and
+/// cancelled proposals are left out. Fails while a proposal is still being revealed.
+pub(crate) fn round_candidates<'info>(
+    proposal_infos: &'info [AccountInfo<'info>],
+    realm_key: &Pubkey,
+    round_id: u64,
+    proposal_ids: &[u64],
+    now: i64,
+) -> Result<Vec<(u64, u64)>> {
+    require!(
+        proposal_infos.len() == proposal_ids.len(),
+        VoteError::IncompleteRoundProposals
+    );
+
+    let mut candidates: Vec<(u64, u64)> = Vec::with_capacity(proposal_ids.len());
+    for (proposal_info, proposal_id) in proposal_infos.iter().zip(proposal_ids.iter()) {
+        let (expected_key, _) = Pubkey::find_program_address(
+            &[
+                b"proposal",
+                realm_key.as_ref(),
+                proposal_id.to_be_bytes().as_ref(),
+            ],
+            &crate::ID,
+        );
+        require_keys_eq!(
+            proposal_info.key(),
+            expected_key,
+            VoteError::ProposalNotInRound
+        );
+        let proposal = Account::<Proposal>::try_from(proposal_info)?;
+        require!(
+            proposal.round_id == Some(round_id),
+            VoteError::ProposalNotInRound
+        );
+        if let Some(reveal_ends_at) = proposal.reveal_ends_at {
+            require!(now >= reveal_ends_at, VoteError::VotingRoundStillOpen);
+        }
+
+        if !matches!(
+            proposal.state,
+            ProposalState::Draft | ProposalState::Cancelled
+        ) {
+            candidates.push((proposal.proposal_id, proposal.approval_weight()));
+        }
+    }
+
+    Ok(candidates)
+}
+
+pub(crate) fn require_round_ended(voting_round: &VotingRound, now: i64) -> Result<()> {
+    require!(
+        voting_round.finalized_at.is_none(),
+        VoteError::VotingRoundAlreadyFinalized
+    );
+    require!(now >= voting_round.ends_at, VoteError::VotingRoundStillOpen);
+    Ok(())
+}
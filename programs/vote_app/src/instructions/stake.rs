@@ -0,0 +1,96 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Transfer as TokenTransfer};
+
+use crate::contexts::{StakeTokens, Unstake};
+use crate::errors::VoteError;
+use crate::events::*;
+use crate::state::MAX_LOCK_DURATION;
+
+/// Locks `amount` tokens in the realm's stake vault until at least `now + lock_duration`.
+/// Staking again adds to the position and can only extend its lock.
+pub fn stake_tokens(ctx: Context<StakeTokens>, amount: u64, lock_duration: i64) -> Result<()> {
+    let clock = Clock::get()?;
+    require!(amount > 0, VoteError::InvalidVoteWeight);
+    require!(
+        lock_duration > 0 && lock_duration <= MAX_LOCK_DURATION,
+        VoteError::InvalidLockDuration
+    );
+
+    let cpi_accounts = TokenTransfer {
+        from: ctx.accounts.staker_token_account.to_account_info(),
+        to: ctx.accounts.stake_vault.to_account_info(),
+        authority: ctx.accounts.authority.to_account_info(),
+    };
+    token::transfer(
+        CpiContext::new(ctx.accounts.token_program.to_account_info(), cpi_accounts),
+        amount,
+    )?;
+
+    let stake_position = &mut ctx.accounts.stake_position;
+    if stake_position.owner == Pubkey::default() {
+        stake_position.owner = ctx.accounts.authority.key();
+        stake_position.locked_at = clock.unix_timestamp;
+        stake_position.bump = ctx.bumps.stake_position;
+    }
+    stake_position.amount = stake_position
+        .amount
+        .checked_add(amount)
+        .ok_or(VoteError::ProposalWeightOverflow)?;
+    stake_position.lock_end = stake_position
+        .lock_end
+        .max(clock.unix_timestamp + lock_duration);
+    stake_position.record_checkpoint(clock.unix_timestamp);
+
+    emit!(TokensStaked {
+        owner: stake_position.owner,
+        amount,
+        total_staked: stake_position.amount,
+        lock_end: stake_position.lock_end,
+        timestamp: clock.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+pub fn unstake(ctx: Context<Unstake>) -> Result<()> {
+    let clock = Clock::get()?;
+    let amount = ctx.accounts.stake_position.amount;
+    require!(
+        clock.unix_timestamp >= ctx.accounts.stake_position.lock_end,
+        VoteError::StakeStillLocked
+    );
+    require!(
+        ctx.accounts.stake_position.conviction_allocated == 0,
+        VoteError::StakeAllocatedToConviction
+    );
+
+    let realm_key = ctx.accounts.realm.key();
+    let escrow_authority_seeds = &[
+        b"escrow_authority".as_ref(),
+        realm_key.as_ref(),
+        &[ctx.bumps.escrow_authority],
+    ];
+    let signer_seeds = &[&escrow_authority_seeds[..]];
+
+    let cpi_accounts = TokenTransfer {
+        from: ctx.accounts.stake_vault.to_account_info(),
+        to: ctx.accounts.staker_token_account.to_account_info(),
+        authority: ctx.accounts.escrow_authority.to_account_info(),
+    };
+    token::transfer(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            cpi_accounts,
+            signer_seeds,
+        ),
+        amount,
+    )?;
+
+    emit!(TokensUnstaked {
+        owner: ctx.accounts.authority.key(),
+        amount,
+        timestamp: clock.unix_timestamp,
+    });
+
+    Ok(())
+}
@@ -2,7 +2,10 @@ use anchor_lang::prelude::*;
 use anchor_lang::system_program::{self, Transfer as SolTransfer};
 use anchor_spl::token;
 
-use crate::contexts::{BuyTokens, ConfigureTreasuryTokenAccount, InitializeTreasury, WithdrawSol};
+use crate::contexts::{
+    BuyTokens, ConfigureTreasuryTokenAccount, InitializeTreasury, WithdrawSol,
+    WithdrawTreasuryTokens,
+};
 use crate::events::*;
 
 pub fn initialize_treasury(
@@ -50,7 +53,12 @@ pub fn buy_tokens(ctx: Context<BuyTokens>) -> Result<()> {
         sol,
     )?;
 
-    let mint_authority_seeds = &[b"mint_authority".as_ref(), &[ctx.bumps.mint_authority]];
+    let realm_key = ctx.accounts.realm.key();
+    let mint_authority_seeds = &[
+        b"mint_authority".as_ref(),
+        realm_key.as_ref(),
+        &[ctx.bumps.mint_authority],
+    ];
     let signer_seeds = &[&mint_authority_seeds[..]];
 
     let cpi_accounts = token::MintTo {
@@ -80,12 +88,17 @@ pub fn buy_tokens(ctx: Context<BuyTokens>) -> Result<()> {
 pub fn withdraw_sol(ctx: Context<WithdrawSol>, amount: u64) -> Result<()> {
     let treasury_config = &ctx.accounts.treasury_config;
 
-    let sol_vault_seeds = &[b"sol_vault".as_ref(), &[treasury_config.bump]];
+    let realm_key = ctx.accounts.realm.key();
+    let sol_vault_seeds = &[
+        b"sol_vault".as_ref(),
+        realm_key.as_ref(),
+        &[treasury_config.bump],
+    ];
     let signer_seeds = &[&sol_vault_seeds[..]];
 
     let transfer_ix = SolTransfer {
         from: ctx.accounts.sol_vault.to_account_info(),
-        to: ctx.accounts.authority.to_account_info(),
+        to: ctx.accounts.recipient.to_account_info(),
     };
 
     system_program::transfer(
@@ -98,7 +111,30 @@ pub fn withdraw_sol(ctx: Context<WithdrawSol>, amount: u64) -> Result<()> {
     )?;
 
     emit!(SolWithdrawn {
-        authority: ctx.accounts.authority.key(),
+        authority: ctx.accounts.governance_authority.key(),
+        recipient: ctx.accounts.recipient.key(),
+        amount,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+pub fn withdraw_treasury_tokens(ctx: Context<WithdrawTreasuryTokens>, amount: u64) -> Result<()> {
+    let cpi_accounts = token::Transfer {
+        from: ctx.accounts.treasury_token_account.to_account_info(),
+        to: ctx.accounts.recipient_token_account.to_account_info(),
+        authority: ctx.accounts.governance_authority.to_account_info(),
+    };
+
+    token::transfer(
+        CpiContext::new(ctx.accounts.token_program.to_account_info(), cpi_accounts),
+        amount,
+    )?;
+
+    emit!(TreasuryTokensWithdrawn {
+        authority: ctx.accounts.governance_authority.key(),
+        recipient_token_account: ctx.accounts.recipient_token_account.key(),
         amount,
         timestamp: Clock::get()?.unix_timestamp,
     });
@@ -9,6 +9,7 @@ pub mod instructions;
 pub mod state;
 
 use contexts::*;
+use state::{GovernanceParams, InstructionAccount};
 
 declare_id!("HDrF2dTrJp5SEvDFy8YEk6E5vivj3DgaBNpUPebdGH9F");
 
@@ -16,6 +17,10 @@ declare_id!("HDrF2dTrJp5SEvDFy8YEk6E5vivj3DgaBNpUPebdGH9F");
 pub mod vote_app {
     use super::*;
 
+    pub fn create_realm(ctx: Context<CreateRealm>, name: String) -> Result<()> {
+        instructions::create_realm(ctx, name)
+    }
+
     pub fn initialize_treasury(
         ctx: Context<InitializeTreasury>,
         sol_price: u64,
@@ -34,6 +39,20 @@ pub mod vote_app {
         instructions::initialize_proposal_counter(ctx)
     }
 
+    pub fn initialize_governance_config(
+        ctx: Context<InitializeGovernanceConfig>,
+        params: GovernanceParams,
+    ) -> Result<()> {
+        instructions::initialize_governance_config(ctx, params)
+    }
+
+    pub fn update_governance_config(
+        ctx: Context<UpdateGovernanceConfig>,
+        params: GovernanceParams,
+    ) -> Result<()> {
+        instructions::update_governance_config(ctx, params)
+    }
+
     pub fn buy_tokens(ctx: Context<BuyTokens>) -> Result<()> {
         instructions::buy_tokens(ctx)
     }
@@ -45,29 +64,217 @@ pub mod vote_app {
     pub fn register_proposal(
         ctx: Context<RegisterProposal>,
         proposal_info: String,
+        options: Vec<String>,
         deadline: i64,
         token_amount: u64,
     ) -> Result<()> {
-        instructions::register_proposal(ctx, proposal_info, deadline, token_amount)
+        instructions::register_proposal(ctx, proposal_info, options, deadline, token_amount)
+    }
+
+    pub fn create_voting_round(
+        ctx: Context<CreateVotingRound>,
+        starts_at: i64,
+        ends_at: i64,
+        ranked_choice: bool,
+    ) -> Result<()> {
+        instructions::create_voting_round(ctx, starts_at, ends_at, ranked_choice)
+    }
+
+    pub fn finalize_round<'info>(
+        ctx: Context<'_, '_, 'info, 'info, FinalizeRound<'info>>,
+        round_id: u64,
+    ) -> Result<()> {
+        instructions::finalize_round(ctx, round_id)
+    }
+
+    pub fn create_runoff_round(
+        ctx: Context<CreateRunoffRound>,
+        round_id: u64,
+        ends_at: i64,
+    ) -> Result<()> {
+        instructions::create_runoff_round(ctx, round_id, ends_at)
+    }
+
+    pub fn submit_ranked_ballot(
+        ctx: Context<SubmitRankedBallot>,
+        round_id: u64,
+        rankings: Vec<u64>,
+    ) -> Result<()> {
+        instructions::submit_ranked_ballot(ctx, round_id, rankings)
+    }
+
+    pub fn start_ranked_tally<'info>(
+        ctx: Context<'_, '_, 'info, 'info, StartRankedTally<'info>>,
+        round_id: u64,
+    ) -> Result<()> {
+        instructions::start_ranked_tally(ctx, round_id)
+    }
+
+    pub fn tally_ranked_ballots<'info>(
+        ctx: Context<'_, '_, 'info, 'info, TallyRankedBallots<'info>>,
+        round_id: u64,
+    ) -> Result<()> {
+        instructions::tally_ranked_ballots(ctx, round_id)
+    }
+
+    pub fn migrate_legacy_proposal(
+        ctx: Context<MigrateLegacyProposal>,
+        legacy_proposal_id: u8,
+    ) -> Result<()> {
+        instructions::migrate_legacy_proposal(ctx, legacy_proposal_id)
+    }
+
+    pub fn add_proposal_instruction(
+        ctx: Context<AddProposalInstruction>,
+        proposal_id: u64,
+        program_id: Pubkey,
+        accounts: Vec<InstructionAccount>,
+        data: Vec<u8>,
+    ) -> Result<()> {
+        instructions::add_proposal_instruction(ctx, proposal_id, program_id, accounts, data)
+    }
+
+    pub fn activate_proposal(ctx: Context<ActivateProposal>, proposal_id: u64) -> Result<()> {
+        instructions::activate_proposal(ctx, proposal_id)
+    }
+
+    pub fn cancel_proposal(ctx: Context<CancelProposal>, proposal_id: u64) -> Result<()> {
+        instructions::cancel_proposal(ctx, proposal_id)
+    }
+
+    pub fn proposal_to_vote<'info>(
+        ctx: Context<'_, '_, 'info, 'info, Vote<'info>>,
+        proposal_id: u64,
+        choice: u8,
+        token_amount: u64,
+    ) -> Result<()> {
+        instructions::proposal_to_vote(ctx, proposal_id, choice, token_amount)
+    }
+
+    pub fn change_vote(ctx: Context<ChangeVote>, proposal_id: u64, new_choice: u8) -> Result<()> {
+        instructions::change_vote(ctx, proposal_id, new_choice)
     }
 
-    pub fn proposal_to_vote(ctx: Context<Vote>, proposal_id: u8, token_amount: u64) -> Result<()> {
-        instructions::proposal_to_vote(ctx, proposal_id, token_amount)
+    pub fn retract_vote(ctx: Context<RetractVote>, proposal_id: u64) -> Result<()> {
+        instructions::retract_vote(ctx, proposal_id)
     }
 
-    pub fn pick_winner(ctx: Context<PickWinner>, proposal_id: u8) -> Result<()> {
+    pub fn commit_vote(
+        ctx: Context<CommitVote>,
+        proposal_id: u64,
+        commitment: [u8; 32],
+    ) -> Result<()> {
+        instructions::commit_vote(ctx, proposal_id, commitment)
+    }
+
+    pub fn reveal_vote(
+        ctx: Context<RevealVote>,
+        proposal_id: u64,
+        choice: u8,
+        tokens: u64,
+        salt: [u8; 32],
+    ) -> Result<()> {
+        instructions::reveal_vote(ctx, proposal_id, choice, tokens, salt)
+    }
+
+    pub fn settle_commitment(ctx: Context<SettleCommitment>, proposal_id: u64) -> Result<()> {
+        instructions::settle_commitment(ctx, proposal_id)
+    }
+
+    pub fn pick_winner(ctx: Context<PickWinner>, proposal_id: u64) -> Result<()> {
         instructions::pick_winner(ctx, proposal_id)
     }
 
-    pub fn close_proposal(ctx: Context<CloseProposal>, proposal_id: u8) -> Result<()> {
+    pub fn execute_proposal<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ExecuteProposal<'info>>,
+        proposal_id: u64,
+    ) -> Result<()> {
+        instructions::execute_proposal(ctx, proposal_id)
+    }
+
+    pub fn withdraw_vote_tokens(ctx: Context<WithdrawVoteTokens>, proposal_id: u64) -> Result<()> {
+        instructions::withdraw_vote_tokens(ctx, proposal_id)
+    }
+
+    pub fn reclaim_proposal_deposit(
+        ctx: Context<ReclaimProposalDeposit>,
+        proposal_id: u64,
+    ) -> Result<()> {
+        instructions::reclaim_proposal_deposit(ctx, proposal_id)
+    }
+
+    pub fn close_proposal<'info>(
+        ctx: Context<'_, '_, 'info, 'info, CloseProposal<'info>>,
+        proposal_id: u64,
+    ) -> Result<()> {
         instructions::close_proposal(ctx, proposal_id)
     }
 
-    pub fn close_voter(ctx: Context<CloseVoter>) -> Result<()> {
+    pub fn close_voter<'info>(ctx: Context<'_, '_, 'info, 'info, CloseVoter<'info>>) -> Result<()> {
         instructions::close_voter(ctx)
     }
 
+    pub fn create_conviction_proposal(
+        ctx: Context<CreateConvictionProposal>,
+        proposal_info: String,
+        beneficiary: Pubkey,
+        requested_lamports: u64,
+    ) -> Result<()> {
+        instructions::create_conviction_proposal(
+            ctx,
+            proposal_info,
+            beneficiary,
+            requested_lamports,
+        )
+    }
+
+    pub fn support_conviction_proposal(
+        ctx: Context<SupportConvictionProposal>,
+        proposal_id: u64,
+        amount: u64,
+    ) -> Result<()> {
+        instructions::support_conviction_proposal(ctx, proposal_id, amount)
+    }
+
+    pub fn withdraw_conviction_support(
+        ctx: Context<WithdrawConvictionSupport>,
+        proposal_id: u64,
+        amount: u64,
+    ) -> Result<()> {
+        instructions::withdraw_conviction_support(ctx, proposal_id, amount)
+    }
+
+    pub fn execute_conviction_proposal(
+        ctx: Context<ExecuteConvictionProposal>,
+        proposal_id: u64,
+    ) -> Result<()> {
+        instructions::execute_conviction_proposal(ctx, proposal_id)
+    }
+
+    pub fn delegate_votes(ctx: Context<DelegateVotes>, amount: u64) -> Result<()> {
+        instructions::delegate_votes(ctx, amount)
+    }
+
+    pub fn undelegate_votes(ctx: Context<UndelegateVotes>) -> Result<()> {
+        instructions::undelegate_votes(ctx)
+    }
+
+    pub fn stake_tokens(ctx: Context<StakeTokens>, amount: u64, lock_duration: i64) -> Result<()> {
+        instructions::stake_tokens(ctx, amount, lock_duration)
+    }
+
+    pub fn unstake(ctx: Context<Unstake>) -> Result<()> {
+        instructions::unstake(ctx)
+    }
+
     pub fn withdraw_sol(ctx: Context<WithdrawSol>, amount: u64) -> Result<()> {
         instructions::withdraw_sol(ctx, amount)
     }
+
+    pub fn withdraw_treasury_tokens(
+        ctx: Context<WithdrawTreasuryTokens>,
+        amount: u64,
+    ) -> Result<()> {
+        instructions::withdraw_treasury_tokens(ctx, amount)
+    }
 }
@@ -1,5 +1,51 @@
 use anchor_lang::prelude::*;
 
+use crate::errors::VoteError;
+
+pub const MIN_PROPOSAL_OPTIONS: usize = 2;
+pub const MAX_PROPOSAL_OPTIONS: usize = 8;
+pub const MAX_OPTION_LABEL_LEN: usize = 32;
+pub const BPS_DENOMINATOR: u64 = 10_000;
+pub const MAX_PROPOSAL_INSTRUCTIONS: u8 = 8;
+pub const MAX_INSTRUCTION_ACCOUNTS: usize = 16;
+pub const MAX_INSTRUCTION_DATA_LEN: usize = 256;
+pub const MAX_REALM_NAME_LEN: usize = 32;
+pub const LEGACY_OPTION_LABEL: &str = "For";
+pub const MAX_ROUND_PROPOSALS: usize = 16;
+pub const MAX_LOCK_DURATION: i64 = 4 * 365 * 24 * 60 * 60;
+pub const MAX_STAKE_CHECKPOINTS: usize = 8;
+
+const ONE_Q32: u128 = 1 << 32;
+const DECAY_FRACTION_BITS: u32 = 16;
+/// `2^(-1 / 2^i)` for `i` in `1..=16`, in Q32 fixed point.
+const FRACTIONAL_HALVINGS_Q32: [u128; DECAY_FRACTION_BITS as usize] = [
+    3_037_000_500,
+    3_611_622_603,
+    3_938_502_376,
+    4_112_874_773,
+    4_202_935_003,
+    4_248_701_965,
+    4_271_771_996,
+    4_283_353_945,
+    4_289_156_690,
+    4_292_061_010,
+    4_293_513_907,
+    4_294_240_540,
+    4_294_603_903,
+    4_294_785_595,
+    4_294_876_445,
+    4_294_921_870,
+];
+
+#[account]
+#[derive(InitSpace)]
+pub struct Realm {
+    pub authority: Pubkey,
+    #[max_len(MAX_REALM_NAME_LEN)]
+    pub name: String,
+    pub bump: u8,
+}
+
 #[account]
 #[derive(InitSpace)]
 pub struct TreasuryConfig {
@@ -11,37 +57,570 @@ pub struct TreasuryConfig {
     pub bump: u8,
 }
 
+#[account]
+#[derive(InitSpace)]
+pub struct GovernanceConfig {
+    pub authority: Pubkey,
+    pub params: GovernanceParams,
+    pub bump: u8,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+pub struct GovernanceParams {
+    pub min_quorum_votes: u64,
+    pub quorum_supply_bps: u16,
+    pub approval_threshold_bps: u16,
+    pub slash_failed_quorum_deposits: bool,
+    pub tie_policy: TiePolicy,
+    pub snapshot_voting: bool,
+    pub voting_mode: VotingMode,
+    pub conviction_half_life: i64,
+    pub conviction_weight_bps: u16,
+    pub conviction_max_ratio_bps: u16,
+    pub commit_reveal: bool,
+    pub reveal_period: i64,
+    pub commit_bond: u64,
+    pub anti_sniping: AntiSnipingPolicy,
+    pub voting_delay: i64,
+    pub min_voting_period: i64,
+    pub max_voting_period: i64,
+}
+
+/// A vote cast within `window` seconds of the deadline that changes the leading option,
+/// or adds more than `threshold_bps` of the weight already cast, pushes the deadline
+/// back by `extension` seconds, at most `max_extensions` times per proposal.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Default, InitSpace)]
+pub struct AntiSnipingPolicy {
+    pub window: i64,
+    pub threshold_bps: u16,
+    pub extension: i64,
+    pub max_extensions: u8,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub enum VotingMode {
+    TokenWeighted,
+    Quadratic,
+}
+
+impl VotingMode {
+    /// Votes counted for `tokens` of committed voting power.
+    pub fn effective_votes(&self, tokens: u64) -> u64 {
+        match self {
+            VotingMode::TokenWeighted => tokens,
+            VotingMode::Quadratic => integer_sqrt(tokens),
+        }
+    }
+}
+
+fn integer_sqrt(n: u64) -> u64 {
+    let n = u128::from(n);
+    if n < 2 {
+        return n as u64;
+    }
+    let mut x = n;
+    let mut y = x.div_ceil(2);
+    while y < x {
+        x = y;
+        y = (x + n / x) / 2;
+    }
+    x as u64
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub enum TiePolicy {
+    EarliestCreated,
+    Runoff,
+    NoWinner,
+}
+
 #[account]
 #[derive(InitSpace)]
 pub struct Voter {
     pub voter_id: Pubkey,
-    pub proposal_voted: u8,
+    pub votes_cast: u64,
 }
 
 #[account]
 #[derive(InitSpace)]
 pub struct Proposal {
-    pub proposal_id: u8,
+    pub proposal_id: u64,
     pub number_of_votes: u64,
+    pub total_weight: u64,
+    pub total_tokens: u64,
+    pub voting_starts_at: i64,
     pub deadline: i64,
+    pub voting_mode: VotingMode,
     #[max_len(50)]
     pub proposal_info: String,
     pub authority: Pubkey,
+    #[max_len(MAX_PROPOSAL_OPTIONS)]
+    pub options: Vec<ProposalOption>,
+    pub winning_option: Option<u8>,
+    pub state: ProposalState,
+    pub quorum_votes: u64,
+    pub quorum_tokens: u64,
+    pub approval_threshold_bps: u16,
+    pub instruction_count: u8,
+    pub instructions_executed: u8,
+    pub defeat_reason: Option<DefeatReason>,
+    pub deposit_amount: u64,
+    pub escrowed_tokens: u64,
+    pub slash_failed_quorum_deposit: bool,
+    pub round_id: Option<u64>,
+    pub tie_policy: TiePolicy,
+    pub snapshot_at: i64,
+    pub snapshot_voting: bool,
+    pub reveal_ends_at: Option<i64>,
+    pub commit_bond: u64,
+    pub unrevealed_commitments: u64,
+    pub anti_sniping: AntiSnipingPolicy,
+    pub extensions: u8,
+}
+
+/// Layout of proposals created before realms, stored under `[b"proposal", u8]`.
+#[derive(AnchorDeserialize)]
+pub struct LegacyProposal {
+    pub proposal_id: u8,
+    pub number_of_votes: u64,
+    pub deadline: i64,
+    pub proposal_info: String,
+    pub authority: Pubkey,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub enum ProposalState {
+    Draft,
+    Active,
+    Succeeded,
+    Defeated,
+    Executed,
+    Cancelled,
+    Migrated,
+}
+
+impl ProposalState {
+    pub fn can_transition_to(self, next: ProposalState) -> bool {
+        matches!(
+            (self, next),
+            (ProposalState::Draft, ProposalState::Active)
+                | (ProposalState::Draft, ProposalState::Cancelled)
+                | (ProposalState::Active, ProposalState::Succeeded)
+                | (ProposalState::Active, ProposalState::Defeated)
+                | (ProposalState::Active, ProposalState::Cancelled)
+                | (ProposalState::Succeeded, ProposalState::Executed)
+        )
+    }
+
+    pub fn is_settled(self) -> bool {
+        matches!(
+            self,
+            ProposalState::Succeeded
+                | ProposalState::Defeated
+                | ProposalState::Executed
+                | ProposalState::Cancelled
+                | ProposalState::Migrated
+        )
+    }
+
+    pub fn is_closable(self) -> bool {
+        matches!(
+            self,
+            ProposalState::Defeated
+                | ProposalState::Executed
+                | ProposalState::Cancelled
+                | ProposalState::Migrated
+        )
+    }
+}
+
+impl Proposal {
+    pub fn transition_to(&mut self, next: ProposalState) -> Result<()> {
+        require!(
+            self.state.can_transition_to(next),
+            VoteError::InvalidStateTransition
+        );
+        self.state = next;
+        Ok(())
+    }
+
+    pub fn accepts_votes(&self, now: i64) -> bool {
+        self.state == ProposalState::Active && now < self.deadline
+    }
+
+    /// Weight cast for the proposal's first option, which is its approving option (e.g.
+    /// "Yes"). Voting rounds rank their proposals by it.
+    pub fn approval_weight(&self) -> u64 {
+        self.options.first().map_or(0, |option| option.weight)
+    }
+
+    /// Whether the proposal's instructions may run: it succeeded and its approving option
+    /// won. A proposal won by any other option resolves without executing anything.
+    pub fn is_approved(&self) -> bool {
+        self.state == ProposalState::Succeeded && self.winning_option == Some(0)
+    }
+
+    /// The option with strictly the most weight, or `None` while nothing is cast or the
+    /// top options are tied.
+    pub fn leading_option(&self) -> Option<u8> {
+        let top_weight = self.options.iter().map(|option| option.weight).max()?;
+        let mut leaders = self
+            .options
+            .iter()
+            .enumerate()
+            .filter(|(_, option)| option.weight == top_weight);
+        match (leaders.next(), leaders.next()) {
+            (Some((index, _)), None) if top_weight > 0 => Some(index as u8),
+            _ => None,
+        }
+    }
+
+    /// Adds one vote of `weight`, backed by `tokens` of voting power, to `choice` and
+    /// returns the option's new weight.
+    pub fn add_vote(&mut self, choice: u8, tokens: u64, weight: u64) -> Result<u64> {
+        let option = &mut self.options[usize::from(choice)];
+        option.votes = option
+            .votes
+            .checked_add(1)
+            .ok_or(VoteError::ProposalVotesOverflow)?;
+        option.weight = option
+            .weight
+            .checked_add(weight)
+            .ok_or(VoteError::ProposalWeightOverflow)?;
+        let option_weight = option.weight;
+
+        self.number_of_votes = self
+            .number_of_votes
+            .checked_add(1)
+            .ok_or(VoteError::ProposalVotesOverflow)?;
+        self.total_weight = self
+            .total_weight
+            .checked_add(weight)
+            .ok_or(VoteError::ProposalWeightOverflow)?;
+        self.total_tokens = self
+            .total_tokens
+            .checked_add(tokens)
+            .ok_or(VoteError::ProposalWeightOverflow)?;
+        Ok(option_weight)
+    }
+
+    pub fn remove_vote(&mut self, choice: u8, tokens: u64, weight: u64) -> Result<()> {
+        let option = &mut self.options[usize::from(choice)];
+        option.votes = option
+            .votes
+            .checked_sub(1)
+            .ok_or(VoteError::ProposalVotesOverflow)?;
+        option.weight = option
+            .weight
+            .checked_sub(weight)
+            .ok_or(VoteError::ProposalWeightOverflow)?;
+
+        self.number_of_votes = self
+            .number_of_votes
+            .checked_sub(1)
+            .ok_or(VoteError::ProposalVotesOverflow)?;
+        self.total_weight = self
+            .total_weight
+            .checked_sub(weight)
+            .ok_or(VoteError::ProposalWeightOverflow)?;
+        self.total_tokens = self
+            .total_tokens
+            .checked_sub(tokens)
+            .ok_or(VoteError::ProposalWeightOverflow)?;
+        Ok(())
+    }
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct ProposalResult {
+    pub proposal_id: u64,
+    pub outcome: ProposalState,
+    pub winning_option: Option<u8>,
+    pub defeat_reason: Option<DefeatReason>,
+    #[max_len(MAX_PROPOSAL_OPTIONS)]
+    pub options: Vec<ProposalOption>,
+    pub total_votes: u64,
+    pub total_weight: u64,
+    pub resolved_by: Pubkey,
+    pub resolved_at: i64,
+    pub unrevealed_commitments: u64,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub enum DefeatReason {
+    QuorumNotReached,
+    ApprovalThresholdNotMet,
+    Tie,
+    LostRound,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+pub struct ProposalOption {
+    #[max_len(MAX_OPTION_LABEL_LEN)]
+    pub label: String,
+    pub votes: u64,
+    pub weight: u64,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct ProposalInstruction {
+    pub proposal_id: u64,
+    pub index: u8,
+    pub program_id: Pubkey,
+    #[max_len(MAX_INSTRUCTION_ACCOUNTS)]
+    pub accounts: Vec<InstructionAccount>,
+    #[max_len(MAX_INSTRUCTION_DATA_LEN)]
+    pub data: Vec<u8>,
+    pub executed_at: Option<i64>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+pub struct InstructionAccount {
+    pub pubkey: Pubkey,
+    pub is_signer: bool,
+    pub is_writable: bool,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct VoteRecord {
+    pub proposal_id: u64,
+    pub voter: Pubkey,
+    pub choice: u8,
+    pub weight: u64,
+    pub tokens: u64,
+    pub tokens_locked: u64,
+    /// Part of `tokens_locked` a delegate escrowed through the voter's delegation.
+    pub delegated_tokens: u64,
+    pub voted_at: i64,
+    pub cast_by: Pubkey,
+    /// Paid the record's rent, and gets it back when the record is closed.
+    pub rent_payer: Pubkey,
+}
+
+/// Hidden vote of a commit-reveal proposal:
+/// `sha256(proposal_id || voter || choice || tokens || salt)`, with `proposal_id` and
+/// `tokens` as little-endian `u64`. Binding the proposal and voter keeps a commitment
+/// from being copied by another wallet or replayed on another proposal.
+#[account]
+#[derive(InitSpace)]
+pub struct VoteCommitment {
+    pub proposal_id: u64,
+    pub voter: Pubkey,
+    pub commitment: [u8; 32],
+    pub bond: u64,
+    pub committed_at: i64,
+    pub revealed: bool,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct Delegation {
+    pub delegator: Pubkey,
+    pub delegate: Pubkey,
+    /// Token allowance the delegation is restored to as escrowed votes are refunded.
+    pub amount: u64,
+    pub delegated_at: i64,
+    pub bump: u8,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct StakePosition {
+    pub owner: Pubkey,
+    pub amount: u64,
+    pub locked_at: i64,
+    pub lock_end: i64,
+    pub conviction_allocated: u64,
+    #[max_len(MAX_STAKE_CHECKPOINTS)]
+    pub checkpoints: Vec<StakeCheckpoint>,
+    pub bump: u8,
+}
+
+impl StakePosition {
+    /// Records the position as of `now`. Once the history is full the second-oldest
+    /// checkpoint is dropped; stakes only grow, so lookups in the gap read the older,
+    /// smaller checkpoint and never overstate past power.
+    pub fn record_checkpoint(&mut self, now: i64) {
+        let checkpoint = StakeCheckpoint {
+            timestamp: now,
+            amount: self.amount,
+            lock_end: self.lock_end,
+        };
+        if let Some(last) = self.checkpoints.last_mut() {
+            if last.timestamp == now {
+                *last = checkpoint;
+                return;
+            }
+        }
+        if self.checkpoints.len() == MAX_STAKE_CHECKPOINTS {
+            self.checkpoints.remove(1);
+        }
+        self.checkpoints.push(checkpoint);
+    }
+
+    /// The position as it stood strictly before `snapshot_at`.
+    pub fn checkpoint_before(&self, snapshot_at: i64) -> Option<&StakeCheckpoint> {
+        self.checkpoints
+            .iter()
+            .rev()
+            .find(|checkpoint| checkpoint.timestamp < snapshot_at)
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, InitSpace)]
+pub struct StakeCheckpoint {
+    pub timestamp: i64,
+    pub amount: u64,
+    pub lock_end: i64,
+}
+
+impl StakeCheckpoint {
+    /// Voting power for a proposal closing at `deadline`: the staked amount scaled by
+    /// the share of `MAX_LOCK_DURATION` the lock still has left at that point.
+    pub fn voting_power_at(&self, deadline: i64) -> u64 {
+        let remaining = self
+            .lock_end
+            .saturating_sub(deadline)
+            .clamp(0, MAX_LOCK_DURATION);
+        (u128::from(self.amount) * remaining as u128 / MAX_LOCK_DURATION as u128) as u64
+    }
 }
 
 #[account]
 #[derive(InitSpace)]
 pub struct ProposalCounter {
     pub authority: Pubkey,
-    pub proposal_count: u8,
+    pub proposal_count: u64,
+    pub round_count: u64,
+    pub conviction_count: u64,
 }
 
+/// Funding request decided by conviction instead of a deadline. Supporters place
+/// staked tokens on it, and its conviction moves toward the staked total with the
+/// configured half-life.
 #[account]
 #[derive(InitSpace)]
-pub struct Winner {
-    pub winning_proposal_id: u8,
-    pub winning_votes: u64,
+pub struct ConvictionProposal {
+    pub proposal_id: u64,
+    pub authority: Pubkey,
+    pub beneficiary: Pubkey,
+    pub requested_lamports: u64,
     #[max_len(50)]
     pub proposal_info: String,
-    pub declared_at: i64,
+    pub staked_tokens: u64,
+    pub conviction: u64,
+    pub half_life: i64,
+    pub weight_bps: u16,
+    pub max_ratio_bps: u16,
+    pub last_updated_at: i64,
+    pub executed_at: Option<i64>,
+}
+
+impl ConvictionProposal {
+    /// Brings conviction up to `now`: `y(t) = x + (y0 - x) * 2^(-t / half_life)`, where
+    /// `x` is the currently staked support and `y0` the conviction at the last update.
+    pub fn accrue(&mut self, now: i64) {
+        let elapsed = now.saturating_sub(self.last_updated_at).max(0);
+        let factor = decay_factor(elapsed, self.half_life);
+        self.conviction = ((u128::from(self.staked_tokens) * (ONE_Q32 - factor)
+            + u128::from(self.conviction) * factor)
+            >> 32) as u64;
+        self.last_updated_at = now;
+    }
+}
+
+/// `2^(-elapsed / half_life)` in Q32 fixed point.
+fn decay_factor(elapsed: i64, half_life: i64) -> u128 {
+    let halvings = elapsed / half_life;
+    if halvings >= 32 {
+        return 0;
+    }
+    let mut factor = ONE_Q32 >> halvings;
+    let fraction = (((elapsed % half_life) as u128) << DECAY_FRACTION_BITS) / half_life as u128;
+    for (bit, multiplier) in FRACTIONAL_HALVINGS_Q32.iter().enumerate() {
+        if fraction & (1 << (DECAY_FRACTION_BITS as usize - 1 - bit)) != 0 {
+            factor = (factor * multiplier) >> 32;
+        }
+    }
+    factor
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct ConvictionSupport {
+    pub proposal_id: u64,
+    pub supporter: Pubkey,
+    pub amount: u64,
+    pub bump: u8,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct VotingRound {
+    pub round_id: u64,
+    pub authority: Pubkey,
+    pub starts_at: i64,
+    pub ends_at: i64,
+    #[max_len(MAX_ROUND_PROPOSALS)]
+    pub proposal_ids: Vec<u64>,
+    pub winning_proposal_id: Option<u64>,
+    pub winning_weight: u64,
+    pub finalized_at: Option<i64>,
+    pub tie_policy: TiePolicy,
+    #[max_len(MAX_ROUND_PROPOSALS)]
+    pub tied_proposal_ids: Vec<u64>,
+    pub ranked_choice: bool,
+    pub ballot_count: u32,
+    /// Set on a runoff round to the round whose tie it settles.
+    pub runoff_of: Option<u64>,
+    /// Set on a round tied under `Runoff` once its runoff round has been created.
+    pub runoff_round_id: Option<u64>,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct RankedBallot {
+    pub round_id: u64,
+    pub voter: Pubkey,
+    #[max_len(MAX_ROUND_PROPOSALS)]
+    pub rankings: Vec<u64>,
+    pub weight: u64,
+    pub last_counted_pass: u32,
+    pub submitted_at: i64,
+}
+
+/// Progress of an instant-runoff count. Each pass credits every ballot to its highest
+/// ranked remaining candidate; `weights` is parallel to `candidates`.
+#[account]
+#[derive(InitSpace)]
+pub struct RankedTally {
+    pub round_id: u64,
+    pub pass: u32,
+    #[max_len(MAX_ROUND_PROPOSALS)]
+    pub candidates: Vec<u64>,
+    #[max_len(MAX_ROUND_PROPOSALS)]
+    pub weights: Vec<u64>,
+    pub exhausted_weight: u64,
+    pub ballots_counted: u32,
+    pub bump: u8,
+}
+
+impl VotingRound {
+    pub fn is_open(&self, now: i64) -> bool {
+        self.starts_at <= now && now < self.ends_at
+    }
+
+    /// The round whose proposals this round decides between: itself, or for a runoff
+    /// round the tied round the proposals were registered in.
+    pub fn member_round_id(&self) -> u64 {
+        self.runoff_of.unwrap_or(self.round_id)
+    }
+
+    /// Whether `proposal_id` tied for this round's win and awaits a runoff round.
+    pub fn awaits_runoff(&self, proposal_id: u64) -> bool {
+        self.tie_policy == TiePolicy::Runoff && self.tied_proposal_ids.contains(&proposal_id)
+    }
 }
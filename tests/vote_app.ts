@@ -4,6 +4,7 @@ import type { VoteApp } from "../target/types/vote_app";
 import idl from "../target/idl/vote_app.json";
 
 import { expect } from "chai";
+import { createHash } from "crypto";
 import {
   createMint,
   getAccount,
@@ -12,20 +13,69 @@ import {
 import NodeWallet from "@anchor-lang/core/dist/cjs/nodewallet";
 
 const SEEDS = {
+  REALM: "realm",
   SOL_VAULT: "sol_vault",
   TREASURY_CONFIG: "treasury_config",
   X_MINT: "x_mint",
   VOTER: "voter",
   PROPOSAL_COUNTER: "proposal_counter",
   PROPOSAL: "proposal",
-  WINNER: "winner",
+  VOTE_RECORD: "vote",
+  PROPOSAL_INSTRUCTION: "proposal_instruction",
+  PROPOSAL_VAULT: "proposal_vault",
+  PROPOSAL_RESULT: "proposal_result",
+  VOTING_ROUND: "voting_round",
+  DELEGATION: "delegate",
+  STAKE_POSITION: "stake",
+  RANKED_BALLOT: "ballot",
+  RANKED_TALLY: "ranked_tally",
+  CONVICTION_PROPOSAL: "conviction_proposal",
+  CONVICTION_SUPPORT: "conviction_support",
+  COMMITMENT: "commitment",
+  TREASURY_TOKENS: "treasury_tokens",
+  GOVERNANCE: "governance",
 } as const;
 
+const REALM_NAME = "engineering";
 const ONE_SOL = anchor.web3.LAMPORTS_PER_SOL;
 const TOKEN_DECIMALS = 6;
+const MAX_LOCK_DURATION_SEC = 4 * 365 * 24 * 60 * 60;
 const TOKENS_PER_PURCHASE_BASE = 1_000_000_000; // 1000 tokens with 6 decimals
 const PROPOSAL_STAKE_BASE = 1_000;
 const VOTE_STAKE_BASE = 1_000;
+const DEFAULT_OPTIONS = ["Yes", "No", "Abstain"];
+const YES_CHOICE = 0;
+const MIN_QUORUM_VOTES = 1;
+const QUORUM_SUPPLY_BPS = 0;
+const APPROVAL_THRESHOLD_BPS = 5_000;
+const MIN_VOTING_PERIOD_SEC = 1;
+const MAX_VOTING_PERIOD_SEC = 30 * 24 * 60 * 60;
+
+const governanceParams = (overrides: Record<string, unknown> = {}) => ({
+  minQuorumVotes: new anchor.BN(MIN_QUORUM_VOTES),
+  quorumSupplyBps: QUORUM_SUPPLY_BPS,
+  approvalThresholdBps: APPROVAL_THRESHOLD_BPS,
+  slashFailedQuorumDeposits: true,
+  tiePolicy: { earliestCreated: {} },
+  snapshotVoting: false,
+  votingMode: { tokenWeighted: {} },
+  convictionHalfLife: new anchor.BN(0),
+  convictionWeightBps: 0,
+  convictionMaxRatioBps: 0,
+  commitReveal: false,
+  revealPeriod: new anchor.BN(0),
+  commitBond: new anchor.BN(0),
+  antiSniping: {
+    window: new anchor.BN(0),
+    thresholdBps: 0,
+    extension: new anchor.BN(0),
+    maxExtensions: 0,
+  },
+  votingDelay: new anchor.BN(0),
+  minVotingPeriod: new anchor.BN(MIN_VOTING_PERIOD_SEC),
+  maxVotingPeriod: new anchor.BN(MAX_VOTING_PERIOD_SEC),
+  ...overrides,
+});
 
 const findPda = (
   programId: anchor.web3.PublicKey,
@@ -35,6 +85,9 @@ const findPda = (
   return pda;
 };
 
+const proposalIdSeed = (proposalId: number) =>
+  new anchor.BN(proposalId).toArrayLike(Buffer, "be", 8);
+
 const sleep = async (ms: number) =>
   new Promise((resolve) => setTimeout(resolve, ms));
 
@@ -161,11 +214,12 @@ describe("vote_app protocol edge-case suite", () => {
   let voterWallet: anchor.web3.Keypair;
   let strangerWallet: anchor.web3.Keypair;
 
+  let realmPda: anchor.web3.PublicKey;
   let treasuryConfigPda: anchor.web3.PublicKey;
   let proposalCounterPda: anchor.web3.PublicKey;
-  let winnerPda: anchor.web3.PublicKey;
   let xMintPda: anchor.web3.PublicKey;
   let solVaultPda: anchor.web3.PublicKey;
+  let governancePda: anchor.web3.PublicKey;
   let voterPda: anchor.web3.PublicKey;
 
   let treasuryTokenAccount: anchor.web3.PublicKey;
@@ -175,13 +229,166 @@ describe("vote_app protocol edge-case suite", () => {
 
   let votedProposalId = 0;
   let votedProposalPda: anchor.web3.PublicKey;
+  let secondVotedProposalId = 0;
+  let defeatedProposalId = 0;
 
   const programAccounts = program.account as unknown as {
+    treasuryConfig: { fetch: (key: anchor.web3.PublicKey) => Promise<any> };
     proposalCounter: { fetch: (key: anchor.web3.PublicKey) => Promise<any> };
     proposal: { fetch: (key: anchor.web3.PublicKey) => Promise<any> };
-    winner: { fetch: (key: anchor.web3.PublicKey) => Promise<any> };
+    proposalResult: { fetch: (key: anchor.web3.PublicKey) => Promise<any> };
+    votingRound: { fetch: (key: anchor.web3.PublicKey) => Promise<any> };
+    rankedBallot: { fetch: (key: anchor.web3.PublicKey) => Promise<any> };
+    rankedTally: { fetch: (key: anchor.web3.PublicKey) => Promise<any> };
+    voteCommitment: { fetch: (key: anchor.web3.PublicKey) => Promise<any> };
+    convictionProposal: { fetch: (key: anchor.web3.PublicKey) => Promise<any> };
+    stakePosition: { fetch: (key: anchor.web3.PublicKey) => Promise<any> };
+    delegation: { fetch: (key: anchor.web3.PublicKey) => Promise<any> };
+    voteRecord: { fetch: (key: anchor.web3.PublicKey) => Promise<any> };
+    voter: { fetch: (key: anchor.web3.PublicKey) => Promise<any> };
   };
 
+  const proposalPdaFor = (proposalId: number) =>
+    findPda(program.programId, [
+      Buffer.from(SEEDS.PROPOSAL),
+      realmPda.toBuffer(),
+      proposalIdSeed(proposalId),
+    ]);
+
+  const voteRecordPdaFor = (
+    proposalId: number,
+    voter: anchor.web3.PublicKey
+  ) =>
+    findPda(program.programId, [
+      Buffer.from(SEEDS.VOTE_RECORD),
+      realmPda.toBuffer(),
+      proposalIdSeed(proposalId),
+      voter.toBuffer(),
+    ]);
+
+  const proposalVaultPdaFor = (proposalId: number) =>
+    findPda(program.programId, [
+      Buffer.from(SEEDS.PROPOSAL_VAULT),
+      realmPda.toBuffer(),
+      proposalIdSeed(proposalId),
+    ]);
+
+  const delegationPdaFor = (delegator: anchor.web3.PublicKey) =>
+    findPda(program.programId, [
+      Buffer.from(SEEDS.DELEGATION),
+      realmPda.toBuffer(),
+      delegator.toBuffer(),
+    ]);
+
+  const stakePositionPdaFor = (owner: anchor.web3.PublicKey) =>
+    findPda(program.programId, [
+      Buffer.from(SEEDS.STAKE_POSITION),
+      realmPda.toBuffer(),
+      owner.toBuffer(),
+    ]);
+
+  const votingRoundPdaFor = (roundId: number) =>
+    findPda(program.programId, [
+      Buffer.from(SEEDS.VOTING_ROUND),
+      realmPda.toBuffer(),
+      proposalIdSeed(roundId),
+    ]);
+
+  const proposalInstructionPdaFor = (proposalId: number, index: number) =>
+    findPda(program.programId, [
+      Buffer.from(SEEDS.PROPOSAL_INSTRUCTION),
+      realmPda.toBuffer(),
+      proposalIdSeed(proposalId),
+      Buffer.from([index]),
+    ]);
+
+  const voterPdaFor = (voter: anchor.web3.PublicKey) =>
+    findPda(program.programId, [
+      Buffer.from(SEEDS.VOTER),
+      realmPda.toBuffer(),
+      voter.toBuffer(),
+    ]);
+
+  const voteBuilder = (
+    voter: anchor.web3.Keypair,
+    voterAta: anchor.web3.PublicKey,
+    proposalId: number,
+    choice: number,
+    tokenAmountBase: number,
+    stakePosition: anchor.web3.PublicKey | null = null
+  ) =>
+    program.methods
+      .proposalToVote(
+        new anchor.BN(proposalId),
+        choice,
+        new anchor.BN(tokenAmountBase)
+      )
+      .accounts({
+        realm: realmPda,
+        authority: voter.publicKey,
+        voterAccount: voterPdaFor(voter.publicKey),
+        voteRecord: voteRecordPdaFor(proposalId, voter.publicKey),
+        stakePosition,
+        xMint: xMintPda,
+        voterTokenAccount: voterAta,
+        proposalVault: proposalVaultPdaFor(proposalId),
+        proposalAccount: proposalPdaFor(proposalId),
+      })
+      .signers([voter]);
+
+  const closeProposalBuilder = (
+    proposalId: number,
+    authority: anchor.web3.Keypair,
+    destination = authority.publicKey
+  ) =>
+    program.methods
+      .closeProposal(new anchor.BN(proposalId))
+      .accounts({
+        realm: realmPda,
+        proposalAccount: proposalPdaFor(proposalId),
+        proposalVault: proposalVaultPdaFor(proposalId),
+        treasuryTokenAccount,
+        destination,
+        authority: authority.publicKey,
+      })
+      .signers([authority]);
+
+  const withdrawVoteTokensBuilder = (
+    voter: anchor.web3.Keypair,
+    voterAta: anchor.web3.PublicKey,
+    proposalId: number,
+    delegation: anchor.web3.PublicKey | null = null
+  ) =>
+    program.methods
+      .withdrawVoteTokens(new anchor.BN(proposalId))
+      .accounts({
+        realm: realmPda,
+        authority: voter.publicKey,
+        proposalAccount: proposalPdaFor(proposalId),
+        voteRecord: voteRecordPdaFor(proposalId, voter.publicKey),
+        proposalVault: proposalVaultPdaFor(proposalId),
+        voterTokenAccount: voterAta,
+        delegation,
+      })
+      .signers([voter]);
+
+  const reclaimDepositBuilder = (
+    creator: anchor.web3.Keypair,
+    creatorAta: anchor.web3.PublicKey,
+    proposalId: number
+  ) =>
+    program.methods
+      .reclaimProposalDeposit(new anchor.BN(proposalId))
+      .accounts({
+        realm: realmPda,
+        authority: creator.publicKey,
+        proposalAccount: proposalPdaFor(proposalId),
+        proposalVault: proposalVaultPdaFor(proposalId),
+        creatorTokenAccount: creatorAta,
+        treasuryTokenAccount,
+      })
+      .signers([creator]);
+
   const buyTokensFor = async (
     wallet: anchor.web3.Keypair,
     buyerTokenAccount: anchor.web3.PublicKey
@@ -190,6 +397,7 @@ describe("vote_app protocol edge-case suite", () => {
       program.methods
         .buyTokens()
         .accounts({
+          realm: realmPda,
           buyer: wallet.publicKey,
           treasuryTokenAccount,
           buyerTokenAccount,
@@ -205,35 +413,53 @@ describe("vote_app protocol edge-case suite", () => {
     creatorAta: anchor.web3.PublicKey,
     deadlineOffsetSec: number,
     proposalInfo: string,
-    tokenStakeBase = PROPOSAL_STAKE_BASE
+    tokenStakeBase = PROPOSAL_STAKE_BASE,
+    options = DEFAULT_OPTIONS,
+    activate = true,
+    votingRound: anchor.web3.PublicKey | null = null
   ) => {
     const counter = await programAccounts.proposalCounter.fetch(proposalCounterPda);
     const proposalId = Number(counter.proposalCount);
-    const proposalPda = findPda(program.programId, [
-      Buffer.from(SEEDS.PROPOSAL),
-      Buffer.from([proposalId]),
-    ]);
+    const proposalPda = proposalPdaFor(proposalId);
     const deadlineTs = (await getBlockTime(connection)) + deadlineOffsetSec;
 
     await retryOnUnknownAction(() =>
       program.methods
         .registerProposal(
           proposalInfo,
+          options,
           new anchor.BN(deadlineTs),
           new anchor.BN(tokenStakeBase)
         )
         .accounts({
+          realm: realmPda,
           authority: creator.publicKey,
           proposalAccount: proposalPda,
           proposalCounterAccount: proposalCounterPda,
           xMint: xMintPda,
           proposalTokenAccount: creatorAta,
-          treasuryTokenAccount,
+          proposalVault: proposalVaultPdaFor(proposalId),
+          votingRound,
+          roundAuthority: votingRound ? adminWallet.publicKey : null,
         })
-        .signers([creator])
+        .signers(votingRound ? [creator, adminWallet] : [creator])
         .rpc()
     );
 
+    if (activate) {
+      await retryOnUnknownAction(() =>
+        program.methods
+          .activateProposal(new anchor.BN(proposalId))
+          .accounts({
+            realm: realmPda,
+            authority: creator.publicKey,
+            proposalAccount: proposalPda,
+          })
+          .signers([creator])
+          .rpc()
+      );
+    }
+
     return { proposalId, proposalPda, deadlineTs };
   };
 
@@ -244,23 +470,33 @@ describe("vote_app protocol edge-case suite", () => {
     voterWallet = anchor.web3.Keypair.generate();
     strangerWallet = anchor.web3.Keypair.generate();
 
+    realmPda = findPda(program.programId, [
+      anchor.utils.bytes.utf8.encode(SEEDS.REALM),
+      anchor.utils.bytes.utf8.encode(REALM_NAME),
+    ]);
     treasuryConfigPda = findPda(program.programId, [
       anchor.utils.bytes.utf8.encode(SEEDS.TREASURY_CONFIG),
+      realmPda.toBuffer(),
     ]);
     proposalCounterPda = findPda(program.programId, [
       anchor.utils.bytes.utf8.encode(SEEDS.PROPOSAL_COUNTER),
-    ]);
-    winnerPda = findPda(program.programId, [
-      anchor.utils.bytes.utf8.encode(SEEDS.WINNER),
+      realmPda.toBuffer(),
     ]);
     xMintPda = findPda(program.programId, [
       anchor.utils.bytes.utf8.encode(SEEDS.X_MINT),
+      realmPda.toBuffer(),
     ]);
     solVaultPda = findPda(program.programId, [
       anchor.utils.bytes.utf8.encode(SEEDS.SOL_VAULT),
+      realmPda.toBuffer(),
+    ]);
+    governancePda = findPda(program.programId, [
+      anchor.utils.bytes.utf8.encode(SEEDS.GOVERNANCE),
+      realmPda.toBuffer(),
     ]);
     voterPda = findPda(program.programId, [
       anchor.utils.bytes.utf8.encode(SEEDS.VOTER),
+      realmPda.toBuffer(),
       voterWallet.publicKey.toBuffer(),
     ]);
 
@@ -271,24 +507,28 @@ describe("vote_app protocol edge-case suite", () => {
       airDropSol(connection, strangerWallet.publicKey, 10 * ONE_SOL),
     ]);
 
+    await program.methods
+      .createRealm(REALM_NAME)
+      .accounts({
+        authority: adminWallet.publicKey,
+      })
+      .rpc();
+
     await program.methods
       .initializeTreasury(
         new anchor.BN(ONE_SOL),
         new anchor.BN(TOKENS_PER_PURCHASE_BASE)
       )
       .accounts({
+        realm: realmPda,
         authority: adminWallet.publicKey,
       })
       .rpc();
 
-    treasuryTokenAccount = (
-      await getOrCreateAssociatedTokenAccount(
-        connection,
-        adminWallet,
-        xMintPda,
-        adminWallet.publicKey
-      )
-    ).address;
+    treasuryTokenAccount = findPda(program.programId, [
+      anchor.utils.bytes.utf8.encode(SEEDS.TREASURY_TOKENS),
+      realmPda.toBuffer(),
+    ]);
     creatorTokenAccount = (
       await getOrCreateAssociatedTokenAccount(
         connection,
@@ -317,15 +557,23 @@ describe("vote_app protocol edge-case suite", () => {
     await program.methods
       .configureTreasuryTokenAccount()
       .accounts({
+        realm: realmPda,
         authority: adminWallet.publicKey,
-        xMint: xMintPda,
-        treasuryTokenAccount,
       })
       .rpc();
 
     await program.methods
       .initializeProposalCounter()
       .accounts({
+        realm: realmPda,
+        authority: adminWallet.publicKey,
+      })
+      .rpc();
+
+    await program.methods
+      .initializeGovernanceConfig(governanceParams())
+      .accounts({
+        realm: realmPda,
         authority: adminWallet.publicKey,
       })
       .rpc();
@@ -336,6 +584,7 @@ describe("vote_app protocol edge-case suite", () => {
     await program.methods
       .registerVoter()
       .accounts({
+        realm: realmPda,
         authority: voterWallet.publicKey,
         voterAccount: voterPda,
       })
@@ -352,20 +601,71 @@ describe("vote_app protocol edge-case suite", () => {
             new anchor.BN(TOKENS_PER_PURCHASE_BASE)
           )
           .accounts({
+            realm: realmPda,
             authority: adminWallet.publicKey,
           })
           .rpc()
       );
     });
 
+    it("rejects treasury initialization from outside the realm authority", async () => {
+      const grantsRealm = findPda(program.programId, [
+        anchor.utils.bytes.utf8.encode(SEEDS.REALM),
+        anchor.utils.bytes.utf8.encode("grants"),
+      ]);
+      await program.methods
+        .createRealm("grants")
+        .accounts({
+          authority: creatorWallet.publicKey,
+        })
+        .signers([creatorWallet])
+        .rpc();
+
+      await expectTxFailure(
+        program.methods
+          .initializeTreasury(
+            new anchor.BN(ONE_SOL),
+            new anchor.BN(TOKENS_PER_PURCHASE_BASE)
+          )
+          .accounts({
+            realm: grantsRealm,
+            authority: adminWallet.publicKey,
+          })
+          .rpc(),
+        "UnauthorizedAccess"
+      );
+
+      await program.methods
+        .initializeTreasury(
+          new anchor.BN(ONE_SOL),
+          new anchor.BN(TOKENS_PER_PURCHASE_BASE)
+        )
+        .accounts({
+          realm: grantsRealm,
+          authority: creatorWallet.publicKey,
+        })
+        .signers([creatorWallet])
+        .rpc();
+
+      const grantsTreasury = await programAccounts.treasuryConfig.fetch(
+        findPda(program.programId, [
+          anchor.utils.bytes.utf8.encode(SEEDS.TREASURY_CONFIG),
+          grantsRealm.toBuffer(),
+        ])
+      );
+      expect(grantsTreasury.authority.toBase58()).to.equal(
+        creatorWallet.publicKey.toBase58()
+      );
+      expect(grantsTreasury.xMint.toBase58()).to.not.equal(xMintPda.toBase58());
+    });
+
     it("rejects treasury token config from non-authority", async () => {
       await expectTxFailure(
         program.methods
           .configureTreasuryTokenAccount()
           .accounts({
+            realm: realmPda,
             authority: creatorWallet.publicKey,
-            xMint: xMintPda,
-            treasuryTokenAccount: creatorTokenAccount,
           })
           .signers([creatorWallet])
           .rpc(),
@@ -373,17 +673,50 @@ describe("vote_app protocol edge-case suite", () => {
       );
     });
 
-    it("rejects treasury token config with wrong token account owner", async () => {
+    it("creates the treasury token account under the governance PDA", async () => {
+      const treasuryTokens = await getAccount(connection, treasuryTokenAccount);
+      expect(treasuryTokens.owner.toBase58()).to.equal(governancePda.toBase58());
+      expect(treasuryTokens.mint.toBase58()).to.equal(xMintPda.toBase58());
+
       await expectTxFailure(
         program.methods
           .configureTreasuryTokenAccount()
           .accounts({
+            realm: realmPda,
             authority: adminWallet.publicKey,
-            xMint: xMintPda,
-            treasuryTokenAccount: creatorTokenAccount,
           })
+          .rpc()
+      );
+    });
+
+    it("rejects governance config update from non-authority", async () => {
+      await expectTxFailure(
+        program.methods
+          .updateGovernanceConfig(
+            governanceParams({ minQuorumVotes: new anchor.BN(0) })
+          )
+          .accounts({
+            realm: realmPda,
+            authority: creatorWallet.publicKey,
+          })
+          .signers([creatorWallet])
           .rpc(),
-        "InvalidTokenAccountOwner"
+        "UnauthorizedAccess"
+      );
+    });
+
+    it("rejects governance config with thresholds above 100%", async () => {
+      await expectTxFailure(
+        program.methods
+          .updateGovernanceConfig(
+            governanceParams({ approvalThresholdBps: 10_001 })
+          )
+          .accounts({
+            realm: realmPda,
+            authority: adminWallet.publicKey,
+          })
+          .rpc(),
+        "InvalidGovernanceConfig"
       );
     });
 
@@ -400,6 +733,7 @@ describe("vote_app protocol edge-case suite", () => {
         program.methods
           .buyTokens()
           .accounts({
+            realm: realmPda,
             buyer: creatorWallet.publicKey,
             treasuryTokenAccount,
             buyerTokenAccount: voterTokenAccount,
@@ -432,6 +766,7 @@ describe("vote_app protocol edge-case suite", () => {
         program.methods
           .buyTokens()
           .accounts({
+            realm: realmPda,
             buyer: creatorWallet.publicKey,
             treasuryTokenAccount,
             buyerTokenAccount: fakeBuyerAta,
@@ -450,6 +785,7 @@ describe("vote_app protocol edge-case suite", () => {
         program.methods
           .registerVoter()
           .accounts({
+            realm: realmPda,
             authority: voterWallet.publicKey,
             voterAccount: voterPda,
           })
@@ -461,26 +797,27 @@ describe("vote_app protocol edge-case suite", () => {
     it("rejects proposal registration with past deadline", async () => {
       const counter = await programAccounts.proposalCounter.fetch(proposalCounterPda);
       const proposalId = Number(counter.proposalCount);
-      const proposalPda = findPda(program.programId, [
-        Buffer.from(SEEDS.PROPOSAL),
-        Buffer.from([proposalId]),
-      ]);
+      const proposalPda = proposalPdaFor(proposalId);
       const deadlineTs = (await getBlockTime(connection)) - 1;
 
       await expectTxFailure(
         program.methods
           .registerProposal(
             "Past deadline proposal",
+            DEFAULT_OPTIONS,
             new anchor.BN(deadlineTs),
             new anchor.BN(PROPOSAL_STAKE_BASE)
           )
           .accounts({
+            realm: realmPda,
             authority: creatorWallet.publicKey,
             proposalAccount: proposalPda,
             proposalCounterAccount: proposalCounterPda,
             xMint: xMintPda,
             proposalTokenAccount: creatorTokenAccount,
-            treasuryTokenAccount,
+            proposalVault: proposalVaultPdaFor(proposalId),
+            votingRound: null,
+            roundAuthority: null,
           })
           .signers([creatorWallet])
           .rpc(),
@@ -488,6 +825,53 @@ describe("vote_app protocol edge-case suite", () => {
       );
     });
 
+    it("rejects voting period bounds with the minimum above the maximum", async () => {
+      await expectTxFailure(
+        program.methods
+          .updateGovernanceConfig(
+            governanceParams({
+              minVotingPeriod: new anchor.BN(MAX_VOTING_PERIOD_SEC + 1),
+            })
+          )
+          .accounts({
+            realm: realmPda,
+            authority: adminWallet.publicKey,
+          })
+          .rpc(),
+        "InvalidVotingPeriodBounds"
+      );
+    });
+
+    it("rejects proposal registration with a voting period above the DAO maximum", async () => {
+      const counter = await programAccounts.proposalCounter.fetch(proposalCounterPda);
+      const proposalId = Number(counter.proposalCount);
+      const deadlineTs = (await getBlockTime(connection)) + MAX_VOTING_PERIOD_SEC + 60;
+
+      await expectTxFailure(
+        program.methods
+          .registerProposal(
+            "Never-ending proposal",
+            DEFAULT_OPTIONS,
+            new anchor.BN(deadlineTs),
+            new anchor.BN(PROPOSAL_STAKE_BASE)
+          )
+          .accounts({
+            realm: realmPda,
+            authority: creatorWallet.publicKey,
+            proposalAccount: proposalPdaFor(proposalId),
+            proposalCounterAccount: proposalCounterPda,
+            xMint: xMintPda,
+            proposalTokenAccount: creatorTokenAccount,
+            proposalVault: proposalVaultPdaFor(proposalId),
+            votingRound: null,
+            roundAuthority: null,
+          })
+          .signers([creatorWallet])
+          .rpc(),
+        "InvalidVotingPeriod"
+      );
+    });
+
     it("registers proposal and increments proposal counter", async () => {
       const counterBefore = await programAccounts.proposalCounter.fetch(
         proposalCounterPda
@@ -510,121 +894,2291 @@ describe("vote_app protocol edge-case suite", () => {
       );
 
       expect(Number(proposal.proposalId)).to.equal(votedProposalId);
+      expect(proposal.state).to.deep.equal({ active: {} });
       expect(Number(proposal.numberOfVotes)).to.equal(0);
+      expect(Number(proposal.totalWeight)).to.equal(0);
       expect(proposal.proposalInfo).to.equal("Primary governance proposal");
+      expect(
+        proposal.options.map((option: { label: string }) => option.label)
+      ).to.deep.equal(DEFAULT_OPTIONS);
       expect(Number(counterAfter.proposalCount)).to.equal(nextBefore + 1);
     });
 
-    it("casts vote successfully", async () => {
-      const before = (await getAccount(connection, voterTokenAccount)).amount;
-
-      await program.methods
-        .proposalToVote(votedProposalId, new anchor.BN(VOTE_STAKE_BASE))
-        .accounts({
-          authority: voterWallet.publicKey,
-          voterAccount: voterPda,
-          xMint: xMintPda,
-          voterTokenAccount,
-          treasuryTokenAccount,
-          proposalAccount: votedProposalPda,
-        })
-        .signers([voterWallet])
-        .rpc();
-
-      const after = (await getAccount(connection, voterTokenAccount)).amount;
-      const proposal = await programAccounts.proposal.fetch(votedProposalPda);
+    it("rejects migrating a legacy proposal that was never created", async () => {
+      const counter = await programAccounts.proposalCounter.fetch(proposalCounterPda);
+      const proposalId = Number(counter.proposalCount);
 
-      expect(before - after).to.equal(BigInt(VOTE_STAKE_BASE));
-      expect(Number(proposal.numberOfVotes)).to.equal(1);
+      await expectTxFailure(
+        program.methods
+          .migrateLegacyProposal(1)
+          .accounts({
+            realm: realmPda,
+            legacyProposal: findPda(program.programId, [
+              Buffer.from(SEEDS.PROPOSAL),
+              Buffer.from([1]),
+            ]),
+            proposalAccount: proposalPdaFor(proposalId),
+            proposalCounterAccount: proposalCounterPda,
+            proposalVault: proposalVaultPdaFor(proposalId),
+            legacyAuthority: creatorWallet.publicKey,
+            authority: adminWallet.publicKey,
+          })
+          .signers([creatorWallet])
+          .rpc(),
+        "InvalidLegacyProposal"
+      );
     });
 
-    it("rejects double voting from same voter", async () => {
+    it("rejects proposal registration with a single option", async () => {
+      const counter = await programAccounts.proposalCounter.fetch(proposalCounterPda);
+      const proposalId = Number(counter.proposalCount);
+      const deadlineTs = (await getBlockTime(connection)) + 30;
+
       await expectTxFailure(
         program.methods
-          .proposalToVote(votedProposalId, new anchor.BN(VOTE_STAKE_BASE))
+          .registerProposal(
+            "Single option proposal",
+            ["Yes"],
+            new anchor.BN(deadlineTs),
+            new anchor.BN(PROPOSAL_STAKE_BASE)
+          )
           .accounts({
-            authority: voterWallet.publicKey,
-            voterAccount: voterPda,
+            realm: realmPda,
+            authority: creatorWallet.publicKey,
+            proposalAccount: proposalPdaFor(proposalId),
+            proposalCounterAccount: proposalCounterPda,
             xMint: xMintPda,
-            voterTokenAccount,
-            treasuryTokenAccount,
-            proposalAccount: votedProposalPda,
+            proposalTokenAccount: creatorTokenAccount,
+            proposalVault: proposalVaultPdaFor(proposalId),
+            votingRound: null,
+            roundAuthority: null,
           })
-          .signers([voterWallet])
+          .signers([creatorWallet])
           .rpc(),
-        "VoterAlreadyVoted"
+        "InvalidOptionCount"
       );
     });
 
-    it("rejects voting after proposal deadline", async () => {
-      const fastProposal = await createProposal(
-        creatorWallet,
-        creatorTokenAccount,
-        3,
-        "Expires quickly"
+    it("rejects vote for an option that does not exist", async () => {
+      await expectTxFailure(
+        voteBuilder(
+          voterWallet,
+          voterTokenAccount,
+          votedProposalId,
+          DEFAULT_OPTIONS.length,
+          VOTE_STAKE_BASE
+        ).rpc(),
+        "InvalidOptionIndex"
       );
-
-      const lateVoterPda = findPda(program.programId, [
-        Buffer.from(SEEDS.VOTER),
-        strangerWallet.publicKey.toBuffer(),
-      ]);
-      const lateVoterInfo = await connection.getAccountInfo(lateVoterPda);
-      if (!lateVoterInfo) {
-        await retryOnUnknownAction(() =>
-          program.methods
-            .registerVoter()
-            .accounts({
-              authority: strangerWallet.publicKey,
-              voterAccount: lateVoterPda,
-            })
-            .signers([strangerWallet])
-            .rpc()
-        );
-      }
-      await buyTokensFor(strangerWallet, strangerTokenAccount);
-
-      while ((await getBlockTime(connection)) <= fastProposal.deadlineTs) {
-        await sleep(500);
-      }
-
-      const tx = await program.methods
-        .proposalToVote(fastProposal.proposalId, new anchor.BN(VOTE_STAKE_BASE))
-        .accounts({
-          authority: strangerWallet.publicKey,
-          voterAccount: lateVoterPda,
-          xMint: xMintPda,
-          voterTokenAccount: strangerTokenAccount,
-          treasuryTokenAccount,
-          proposalAccount: fastProposal.proposalPda,
-        })
-        .transaction();
-
-      await expectTxFailure(provider.simulate(tx, [strangerWallet]));
     });
-  });
 
-  describe("Winner Selection, Closing, and Withdrawals", () => {
-    it("rejects pickWinner while voting is still active", async () => {
-      const activeProposal = await createProposal(
+    it("keeps draft proposals closed to votes until activated", async () => {
+      const draft = await createProposal(
         creatorWallet,
         creatorTokenAccount,
-        20,
-        "Still active proposal"
+        30,
+        "Draft proposal",
+        PROPOSAL_STAKE_BASE,
+        DEFAULT_OPTIONS,
+        false
+      );
+
+      const stored = await programAccounts.proposal.fetch(draft.proposalPda);
+      expect(stored.state).to.deep.equal({ draft: {} });
+
+      await expectTxFailure(
+        voteBuilder(
+          voterWallet,
+          voterTokenAccount,
+          draft.proposalId,
+          YES_CHOICE,
+          VOTE_STAKE_BASE
+        ).rpc(),
+        "ProposalNotActive"
+      );
+
+      await expectTxFailure(
+        program.methods
+          .activateProposal(new anchor.BN(draft.proposalId))
+          .accounts({
+            realm: realmPda,
+            authority: voterWallet.publicKey,
+            proposalAccount: draft.proposalPda,
+          })
+          .signers([voterWallet])
+          .rpc(),
+        "UnauthorizedAccess"
+      );
+
+      await program.methods
+        .cancelProposal(new anchor.BN(draft.proposalId))
+        .accounts({
+          realm: realmPda,
+          authority: creatorWallet.publicKey,
+          proposalAccount: draft.proposalPda,
+        })
+        .signers([creatorWallet])
+        .rpc();
+
+      const cancelled = await programAccounts.proposal.fetch(draft.proposalPda);
+      expect(cancelled.state).to.deep.equal({ cancelled: {} });
+
+      await expectTxFailure(
+        program.methods
+          .activateProposal(new anchor.BN(draft.proposalId))
+          .accounts({
+            realm: realmPda,
+            authority: creatorWallet.publicKey,
+            proposalAccount: draft.proposalPda,
+          })
+          .signers([creatorWallet])
+          .rpc(),
+        "InvalidStateTransition"
+      );
+    });
+
+    it("rejects vote with zero token weight", async () => {
+      await expectTxFailure(
+        voteBuilder(
+          voterWallet,
+          voterTokenAccount,
+          votedProposalId,
+          YES_CHOICE,
+          0
+        ).rpc(),
+        "InvalidVoteWeight"
+      );
+    });
+
+    it("casts vote successfully", async () => {
+      const before = (await getAccount(connection, voterTokenAccount)).amount;
+
+      await voteBuilder(
+        voterWallet,
+        voterTokenAccount,
+        votedProposalId,
+        YES_CHOICE,
+        VOTE_STAKE_BASE
+      ).rpc();
+
+      const after = (await getAccount(connection, voterTokenAccount)).amount;
+      const proposal = await programAccounts.proposal.fetch(votedProposalPda);
+      const voteRecord = await programAccounts.voteRecord.fetch(
+        voteRecordPdaFor(votedProposalId, voterWallet.publicKey)
+      );
+
+      expect(before - after).to.equal(BigInt(VOTE_STAKE_BASE));
+      expect(Number(proposal.numberOfVotes)).to.equal(1);
+      expect(Number(proposal.totalWeight)).to.equal(VOTE_STAKE_BASE);
+      expect(Number(proposal.options[YES_CHOICE].weight)).to.equal(
+        VOTE_STAKE_BASE
+      );
+      expect(voteRecord.choice).to.equal(YES_CHOICE);
+      expect(voteRecord.voter.toBase58()).to.equal(
+        voterWallet.publicKey.toBase58()
+      );
+      expect(Number(voteRecord.weight)).to.equal(VOTE_STAKE_BASE);
+    });
+
+    it("rejects double voting from same voter", async () => {
+      await expectTxFailure(
+        voteBuilder(
+          voterWallet,
+          voterTokenAccount,
+          votedProposalId,
+          YES_CHOICE,
+          VOTE_STAKE_BASE
+        ).rpc(),
+        "VoterAlreadyVoted"
+      );
+    });
+
+    it("allows the same voter to vote on another proposal", async () => {
+      const second = await createProposal(
+        creatorWallet,
+        creatorTokenAccount,
+        6,
+        "Second proposal for the same voter"
+      );
+      secondVotedProposalId = second.proposalId;
+
+      await voteBuilder(
+        voterWallet,
+        voterTokenAccount,
+        second.proposalId,
+        YES_CHOICE,
+        VOTE_STAKE_BASE
+      ).rpc();
+
+      const proposal = await programAccounts.proposal.fetch(second.proposalPda);
+      const voter = await programAccounts.voter.fetch(voterPda);
+      expect(Number(proposal.numberOfVotes)).to.equal(1);
+      expect(Number(voter.votesCast)).to.equal(2);
+    });
+
+    it("rejects voting after proposal deadline", async () => {
+      const fastProposal = await createProposal(
+        creatorWallet,
+        creatorTokenAccount,
+        3,
+        "Expires quickly"
+      );
+
+      const lateVoterPda = voterPdaFor(strangerWallet.publicKey);
+      const lateVoterInfo = await connection.getAccountInfo(lateVoterPda);
+      if (!lateVoterInfo) {
+        await retryOnUnknownAction(() =>
+          program.methods
+            .registerVoter()
+            .accounts({
+              realm: realmPda,
+              authority: strangerWallet.publicKey,
+              voterAccount: lateVoterPda,
+            })
+            .signers([strangerWallet])
+            .rpc()
+        );
+      }
+      await buyTokensFor(strangerWallet, strangerTokenAccount);
+
+      while ((await getBlockTime(connection)) <= fastProposal.deadlineTs) {
+        await sleep(500);
+      }
+
+      const tx = await voteBuilder(
+        strangerWallet,
+        strangerTokenAccount,
+        fastProposal.proposalId,
+        YES_CHOICE,
+        VOTE_STAKE_BASE
+      ).transaction();
+
+      await expectTxFailure(provider.simulate(tx, [strangerWallet]));
+    });
+  });
+
+  describe("Vote Delegation", () => {
+    let delegatedProposal: { proposalId: number; proposalPda: anchor.web3.PublicKey };
+
+    it("rejects delegating votes to yourself", async () => {
+      await expectTxFailure(
+        program.methods
+          .delegateVotes(new anchor.BN(VOTE_STAKE_BASE))
+          .accounts({
+            realm: realmPda,
+            delegate: voterWallet.publicKey,
+            delegatorTokenAccount: voterTokenAccount,
+            authority: voterWallet.publicKey,
+          })
+          .signers([voterWallet])
+          .rpc(),
+        "InvalidDelegate"
+      );
+    });
+
+    it("lets a delegate vote with its delegators' tokens", async () => {
+      await program.methods
+        .delegateVotes(new anchor.BN(VOTE_STAKE_BASE))
+        .accounts({
+          realm: realmPda,
+          delegate: voterWallet.publicKey,
+          delegatorTokenAccount: strangerTokenAccount,
+          authority: strangerWallet.publicKey,
+        })
+        .signers([strangerWallet])
+        .rpc();
+
+      delegatedProposal = await createProposal(
+        creatorWallet,
+        creatorTokenAccount,
+        60,
+        "Delegated vote proposal"
+      );
+
+      await voteBuilder(
+        voterWallet,
+        voterTokenAccount,
+        delegatedProposal.proposalId,
+        YES_CHOICE,
+        VOTE_STAKE_BASE
+      )
+        .remainingAccounts([
+          {
+            pubkey: delegationPdaFor(strangerWallet.publicKey),
+            isSigner: false,
+            isWritable: false,
+          },
+          {
+            pubkey: voteRecordPdaFor(
+              delegatedProposal.proposalId,
+              strangerWallet.publicKey
+            ),
+            isSigner: false,
+            isWritable: true,
+          },
+          { pubkey: strangerTokenAccount, isSigner: false, isWritable: true },
+        ])
+        .rpc();
+
+      const proposal = await programAccounts.proposal.fetch(
+        delegatedProposal.proposalPda
+      );
+      expect(Number(proposal.numberOfVotes)).to.equal(2);
+      expect(Number(proposal.options[YES_CHOICE].weight)).to.equal(
+        2 * VOTE_STAKE_BASE
+      );
+
+      const record = await programAccounts.voteRecord.fetch(
+        voteRecordPdaFor(delegatedProposal.proposalId, strangerWallet.publicKey)
+      );
+      expect(record.castBy.toBase58()).to.equal(voterWallet.publicKey.toBase58());
+      expect(record.rentPayer.toBase58()).to.equal(voterWallet.publicKey.toBase58());
+      expect(Number(record.tokensLocked)).to.equal(VOTE_STAKE_BASE);
+    });
+
+    it("lets the delegator override its delegate's vote", async () => {
+      await voteBuilder(
+        strangerWallet,
+        strangerTokenAccount,
+        delegatedProposal.proposalId,
+        YES_CHOICE + 1,
+        0
+      ).rpc();
+
+      const proposal = await programAccounts.proposal.fetch(
+        delegatedProposal.proposalPda
+      );
+      expect(Number(proposal.numberOfVotes)).to.equal(2);
+      expect(Number(proposal.options[YES_CHOICE].weight)).to.equal(
+        VOTE_STAKE_BASE
+      );
+      expect(Number(proposal.options[YES_CHOICE + 1].weight)).to.equal(
+        VOTE_STAKE_BASE
+      );
+
+      const record = await programAccounts.voteRecord.fetch(
+        voteRecordPdaFor(delegatedProposal.proposalId, strangerWallet.publicKey)
+      );
+      expect(record.castBy.toBase58()).to.equal(
+        strangerWallet.publicKey.toBase58()
+      );
+
+      await expectTxFailure(
+        voteBuilder(
+          strangerWallet,
+          strangerTokenAccount,
+          delegatedProposal.proposalId,
+          YES_CHOICE,
+          VOTE_STAKE_BASE
+        ).rpc(),
+        "VoterAlreadyVoted"
+      );
+    });
+
+    it("undelegates and revokes the token approval", async () => {
+      await program.methods
+        .undelegateVotes()
+        .accounts({
+          realm: realmPda,
+          delegatorTokenAccount: strangerTokenAccount,
+          authority: strangerWallet.publicKey,
+        })
+        .signers([strangerWallet])
+        .rpc();
+
+      expect(
+        await connection.getAccountInfo(delegationPdaFor(strangerWallet.publicKey))
+      ).to.be.null;
+      const tokenAccount = await getAccount(connection, strangerTokenAccount);
+      expect(tokenAccount.delegate).to.be.null;
+    });
+
+    it("lets a delegate cast for more delegators after its own vote", async () => {
+      const delegateAccounts = {
+        realm: realmPda,
+        delegatorTokenAccount: strangerTokenAccount,
+        authority: strangerWallet.publicKey,
+      };
+      await program.methods
+        .delegateVotes(new anchor.BN(VOTE_STAKE_BASE))
+        .accounts({ ...delegateAccounts, delegate: voterWallet.publicKey })
+        .signers([strangerWallet])
+        .rpc();
+
+      const batchProposal = await createProposal(
+        creatorWallet,
+        creatorTokenAccount,
+        60,
+        "Batched delegation proposal"
+      );
+      await voteBuilder(
+        voterWallet,
+        voterTokenAccount,
+        batchProposal.proposalId,
+        YES_CHOICE,
+        VOTE_STAKE_BASE
+      ).rpc();
+
+      const batchBuilder = (choice: number) =>
+        voteBuilder(voterWallet, voterTokenAccount, batchProposal.proposalId, choice, 0)
+          .remainingAccounts([
+            {
+              pubkey: delegationPdaFor(strangerWallet.publicKey),
+              isSigner: false,
+              isWritable: false,
+            },
+            {
+              pubkey: voteRecordPdaFor(batchProposal.proposalId, strangerWallet.publicKey),
+              isSigner: false,
+              isWritable: true,
+            },
+            { pubkey: strangerTokenAccount, isSigner: false, isWritable: true },
+          ]);
+
+      await expectTxFailure(
+        batchBuilder(YES_CHOICE + 1).rpc(),
+        "DelegateChoiceMismatch"
+      );
+      // Pre-funding the delegator's vote record must not block the delegate.
+      await provider.sendAndConfirm(
+        new anchor.web3.Transaction().add(
+          anchor.web3.SystemProgram.transfer({
+            fromPubkey: adminWallet.publicKey,
+            toPubkey: voteRecordPdaFor(batchProposal.proposalId, strangerWallet.publicKey),
+            lamports: 1_000,
+          })
+        )
+      );
+      await batchBuilder(YES_CHOICE).rpc();
+
+      const proposal = await programAccounts.proposal.fetch(batchProposal.proposalPda);
+      expect(Number(proposal.numberOfVotes)).to.equal(2);
+      expect(Number(proposal.options[YES_CHOICE].weight)).to.equal(
+        2 * VOTE_STAKE_BASE
+      );
+
+      await program.methods
+        .undelegateVotes()
+        .accounts(delegateAccounts)
+        .signers([strangerWallet])
+        .rpc();
+    });
+
+    it("keeps a single delegation working across proposals", async () => {
+      const delegateAccounts = {
+        realm: realmPda,
+        delegatorTokenAccount: strangerTokenAccount,
+        authority: strangerWallet.publicKey,
+      };
+      await program.methods
+        .delegateVotes(new anchor.BN(VOTE_STAKE_BASE))
+        .accounts({ ...delegateAccounts, delegate: voterWallet.publicKey })
+        .signers([strangerWallet])
+        .rpc();
+
+      const delegatedVoteBuilder = (proposalId: number) =>
+        voteBuilder(
+          voterWallet,
+          voterTokenAccount,
+          proposalId,
+          YES_CHOICE,
+          VOTE_STAKE_BASE
+        ).remainingAccounts([
+          {
+            pubkey: delegationPdaFor(strangerWallet.publicKey),
+            isSigner: false,
+            isWritable: false,
+          },
+          {
+            pubkey: voteRecordPdaFor(proposalId, strangerWallet.publicKey),
+            isSigner: false,
+            isWritable: true,
+          },
+          { pubkey: strangerTokenAccount, isSigner: false, isWritable: true },
+        ]);
+
+      const first = await createProposal(
+        creatorWallet,
+        creatorTokenAccount,
+        4,
+        "First proposal of a standing delegation"
+      );
+      await delegatedVoteBuilder(first.proposalId).rpc();
+      expect(
+        Number((await getAccount(connection, strangerTokenAccount)).delegatedAmount)
+      ).to.equal(0);
+
+      while ((await getBlockTime(connection)) <= first.deadlineTs) {
+        await sleep(500);
+      }
+      await program.methods
+        .pickWinner(new anchor.BN(first.proposalId))
+        .accounts({
+          realm: realmPda,
+          authority: adminWallet.publicKey,
+          proposalAccount: first.proposalPda,
+          votingRound: null,
+          runoffRound: null,
+        })
+        .rpc();
+      await withdrawVoteTokensBuilder(
+        strangerWallet,
+        strangerTokenAccount,
+        first.proposalId,
+        delegationPdaFor(strangerWallet.publicKey)
+      ).rpc();
+
+      const tokenAccount = await getAccount(connection, strangerTokenAccount);
+      expect(tokenAccount.delegate?.toBase58()).to.equal(
+        delegationPdaFor(strangerWallet.publicKey).toBase58()
+      );
+      expect(Number(tokenAccount.delegatedAmount)).to.equal(VOTE_STAKE_BASE);
+
+      const second = await createProposal(
+        creatorWallet,
+        creatorTokenAccount,
+        60,
+        "Second proposal of a standing delegation"
+      );
+      await delegatedVoteBuilder(second.proposalId).rpc();
+
+      const proposal = await programAccounts.proposal.fetch(second.proposalPda);
+      expect(Number(proposal.numberOfVotes)).to.equal(2);
+      expect(Number(proposal.options[YES_CHOICE].weight)).to.equal(
+        2 * VOTE_STAKE_BASE
+      );
+      const record = await programAccounts.voteRecord.fetch(
+        voteRecordPdaFor(second.proposalId, strangerWallet.publicKey)
+      );
+      expect(Number(record.delegatedTokens)).to.equal(VOTE_STAKE_BASE);
+
+      await program.methods
+        .undelegateVotes()
+        .accounts(delegateAccounts)
+        .signers([strangerWallet])
+        .rpc();
+    });
+
+    it("leaves the delegator's vote count alone when it retracts a delegated vote", async () => {
+      const delegateAccounts = {
+        realm: realmPda,
+        delegatorTokenAccount: strangerTokenAccount,
+        authority: strangerWallet.publicKey,
+      };
+      await program.methods
+        .delegateVotes(new anchor.BN(VOTE_STAKE_BASE))
+        .accounts({ ...delegateAccounts, delegate: voterWallet.publicKey })
+        .signers([strangerWallet])
+        .rpc();
+
+      const retractedProposal = await createProposal(
+        creatorWallet,
+        creatorTokenAccount,
+        60,
+        "Retracted delegated vote proposal"
+      );
+      await voteBuilder(
+        voterWallet,
+        voterTokenAccount,
+        retractedProposal.proposalId,
+        YES_CHOICE,
+        VOTE_STAKE_BASE
+      )
+        .remainingAccounts([
+          {
+            pubkey: delegationPdaFor(strangerWallet.publicKey),
+            isSigner: false,
+            isWritable: false,
+          },
+          {
+            pubkey: voteRecordPdaFor(
+              retractedProposal.proposalId,
+              strangerWallet.publicKey
+            ),
+            isSigner: false,
+            isWritable: true,
+          },
+          { pubkey: strangerTokenAccount, isSigner: false, isWritable: true },
+        ])
+        .rpc();
+
+      const before = await programAccounts.voter.fetch(
+        voterPdaFor(strangerWallet.publicKey)
+      );
+      await program.methods
+        .retractVote(new anchor.BN(retractedProposal.proposalId))
+        .accounts({
+          realm: realmPda,
+          voterAccount: voterPdaFor(strangerWallet.publicKey),
+          voteRecord: voteRecordPdaFor(
+            retractedProposal.proposalId,
+            strangerWallet.publicKey
+          ),
+          proposalAccount: retractedProposal.proposalPda,
+          proposalVault: proposalVaultPdaFor(retractedProposal.proposalId),
+          voterTokenAccount: strangerTokenAccount,
+          rentPayer: voterWallet.publicKey,
+          delegation: delegationPdaFor(strangerWallet.publicKey),
+          authority: strangerWallet.publicKey,
+        })
+        .signers([strangerWallet])
+        .rpc();
+
+      const after = await programAccounts.voter.fetch(
+        voterPdaFor(strangerWallet.publicKey)
+      );
+      expect(Number(after.votesCast)).to.equal(Number(before.votesCast));
+      const proposal = await programAccounts.proposal.fetch(
+        retractedProposal.proposalPda
+      );
+      expect(Number(proposal.numberOfVotes)).to.equal(1);
+
+      await program.methods
+        .undelegateVotes()
+        .accounts(delegateAccounts)
+        .signers([strangerWallet])
+        .rpc();
+    });
+
+    it("counts no vote of its own for a delegate that adds no weight", async () => {
+      const delegateAccounts = {
+        realm: realmPda,
+        delegatorTokenAccount: strangerTokenAccount,
+        authority: strangerWallet.publicKey,
+      };
+      await program.methods
+        .delegateVotes(new anchor.BN(VOTE_STAKE_BASE))
+        .accounts({ ...delegateAccounts, delegate: voterWallet.publicKey })
+        .signers([strangerWallet])
+        .rpc();
+
+      const proxyProposal = await createProposal(
+        creatorWallet,
+        creatorTokenAccount,
+        60,
+        "Proxy-only delegation proposal"
+      );
+      await voteBuilder(
+        voterWallet,
+        voterTokenAccount,
+        proxyProposal.proposalId,
+        YES_CHOICE,
+        0
+      )
+        .remainingAccounts([
+          {
+            pubkey: delegationPdaFor(strangerWallet.publicKey),
+            isSigner: false,
+            isWritable: false,
+          },
+          {
+            pubkey: voteRecordPdaFor(proxyProposal.proposalId, strangerWallet.publicKey),
+            isSigner: false,
+            isWritable: true,
+          },
+          { pubkey: strangerTokenAccount, isSigner: false, isWritable: true },
+        ])
+        .rpc();
+
+      const proposal = await programAccounts.proposal.fetch(proxyProposal.proposalPda);
+      expect(Number(proposal.numberOfVotes)).to.equal(1);
+      expect(Number(proposal.totalWeight)).to.equal(VOTE_STAKE_BASE);
+
+      const delegateRecord = await programAccounts.voteRecord.fetch(
+        voteRecordPdaFor(proxyProposal.proposalId, voterWallet.publicKey)
+      );
+      expect(delegateRecord.voter.toBase58()).to.equal(
+        anchor.web3.PublicKey.default.toBase58()
+      );
+      expect(Number(delegateRecord.weight)).to.equal(0);
+
+      await expectTxFailure(
+        program.methods
+          .retractVote(new anchor.BN(proxyProposal.proposalId))
+          .accounts({
+            realm: realmPda,
+            voterAccount: voterPdaFor(voterWallet.publicKey),
+            voteRecord: voteRecordPdaFor(proxyProposal.proposalId, voterWallet.publicKey),
+            proposalAccount: proxyProposal.proposalPda,
+            proposalVault: proposalVaultPdaFor(proxyProposal.proposalId),
+            voterTokenAccount,
+            rentPayer: voterWallet.publicKey,
+            delegation: null,
+            authority: voterWallet.publicKey,
+          })
+          .signers([voterWallet])
+          .rpc(),
+        "InvalidVoteRecord"
+      );
+
+      await program.methods
+        .undelegateVotes()
+        .accounts(delegateAccounts)
+        .signers([strangerWallet])
+        .rpc();
+    });
+  });
+
+  describe("Vote Changes", () => {
+    let changedProposal: { proposalId: number; proposalPda: anchor.web3.PublicKey };
+
+    const changeVoteBuilder = (proposalId: number, newChoice: number) =>
+      program.methods
+        .changeVote(new anchor.BN(proposalId), newChoice)
+        .accounts({
+          realm: realmPda,
+          voterAccount: voterPdaFor(voterWallet.publicKey),
+          voteRecord: voteRecordPdaFor(proposalId, voterWallet.publicKey),
+          proposalAccount: proposalPdaFor(proposalId),
+          authority: voterWallet.publicKey,
+        })
+        .signers([voterWallet]);
+
+    it("moves a vote to another option before the deadline", async () => {
+      changedProposal = await createProposal(
+        creatorWallet,
+        creatorTokenAccount,
+        60,
+        "Changeable vote proposal"
+      );
+      await voteBuilder(
+        voterWallet,
+        voterTokenAccount,
+        changedProposal.proposalId,
+        YES_CHOICE,
+        VOTE_STAKE_BASE
+      ).rpc();
+
+      await changeVoteBuilder(changedProposal.proposalId, YES_CHOICE + 1).rpc();
+
+      const proposal = await programAccounts.proposal.fetch(
+        changedProposal.proposalPda
+      );
+      expect(Number(proposal.numberOfVotes)).to.equal(1);
+      expect(Number(proposal.options[YES_CHOICE].weight)).to.equal(0);
+      expect(Number(proposal.options[YES_CHOICE + 1].weight)).to.equal(
+        VOTE_STAKE_BASE
+      );
+
+      const record = await programAccounts.voteRecord.fetch(
+        voteRecordPdaFor(changedProposal.proposalId, voterWallet.publicKey)
+      );
+      expect(record.choice).to.equal(YES_CHOICE + 1);
+    });
+
+    it("rejects changing a vote to the same option", async () => {
+      await expectTxFailure(
+        changeVoteBuilder(changedProposal.proposalId, YES_CHOICE + 1).rpc(),
+        "VoteUnchanged"
+      );
+    });
+
+    it("retracts a vote and refunds its tokens", async () => {
+      const before = await getAccount(connection, voterTokenAccount);
+
+      await program.methods
+        .retractVote(new anchor.BN(changedProposal.proposalId))
+        .accounts({
+          realm: realmPda,
+          voterAccount: voterPdaFor(voterWallet.publicKey),
+          voteRecord: voteRecordPdaFor(
+            changedProposal.proposalId,
+            voterWallet.publicKey
+          ),
+          proposalAccount: changedProposal.proposalPda,
+          proposalVault: proposalVaultPdaFor(changedProposal.proposalId),
+          voterTokenAccount,
+          rentPayer: voterWallet.publicKey,
+          delegation: null,
+          authority: voterWallet.publicKey,
+        })
+        .signers([voterWallet])
+        .rpc();
+
+      const after = await getAccount(connection, voterTokenAccount);
+      expect(Number(after.amount) - Number(before.amount)).to.equal(
+        VOTE_STAKE_BASE
+      );
+
+      const proposal = await programAccounts.proposal.fetch(
+        changedProposal.proposalPda
+      );
+      expect(Number(proposal.numberOfVotes)).to.equal(0);
+      expect(Number(proposal.totalWeight)).to.equal(0);
+      expect(
+        await connection.getAccountInfo(
+          voteRecordPdaFor(changedProposal.proposalId, voterWallet.publicKey)
+        )
+      ).to.be.null;
+
+      await voteBuilder(
+        voterWallet,
+        voterTokenAccount,
+        changedProposal.proposalId,
+        YES_CHOICE,
+        VOTE_STAKE_BASE
+      ).rpc();
+    });
+  });
+
+  describe("Vote-Escrow Staking", () => {
+    const STAKE_AMOUNT_BASE = 1_000_000;
+
+    const stakeBuilder = (
+      staker: anchor.web3.Keypair,
+      stakerAta: anchor.web3.PublicKey,
+      amountBase: number,
+      lockDurationSec: number
+    ) =>
+      program.methods
+        .stakeTokens(new anchor.BN(amountBase), new anchor.BN(lockDurationSec))
+        .accounts({
+          realm: realmPda,
+          xMint: xMintPda,
+          stakerTokenAccount: stakerAta,
+          authority: staker.publicKey,
+        })
+        .signers([staker]);
+
+    const unstakeBuilder = (
+      staker: anchor.web3.Keypair,
+      stakerAta: anchor.web3.PublicKey
+    ) =>
+      program.methods
+        .unstake()
+        .accounts({
+          realm: realmPda,
+          stakerTokenAccount: stakerAta,
+          authority: staker.publicKey,
+        })
+        .signers([staker]);
+
+    it("rejects a lock longer than the maximum", async () => {
+      await expectTxFailure(
+        stakeBuilder(
+          voterWallet,
+          voterTokenAccount,
+          STAKE_AMOUNT_BASE,
+          MAX_LOCK_DURATION_SEC + 1
+        ).rpc(),
+        "InvalidLockDuration"
+      );
+    });
+
+    it("releases staked tokens once the lock expires", async () => {
+      const before = await getAccount(connection, strangerTokenAccount);
+      await stakeBuilder(
+        strangerWallet,
+        strangerTokenAccount,
+        STAKE_AMOUNT_BASE,
+        1
+      ).rpc();
+      await sleep(2_000);
+      await unstakeBuilder(strangerWallet, strangerTokenAccount).rpc();
+
+      const after = await getAccount(connection, strangerTokenAccount);
+      expect(after.amount).to.equal(before.amount);
+      expect(
+        await connection.getAccountInfo(
+          stakePositionPdaFor(strangerWallet.publicKey)
+        )
+      ).to.be.null;
+    });
+
+    it("votes with the power of a locked stake without spending tokens", async () => {
+      await stakeBuilder(
+        voterWallet,
+        voterTokenAccount,
+        STAKE_AMOUNT_BASE,
+        MAX_LOCK_DURATION_SEC
+      ).rpc();
+      await sleep(2_000);
+
+      const stakedProposal = await createProposal(
+        creatorWallet,
+        creatorTokenAccount,
+        60,
+        "Staked vote proposal"
+      );
+      const before = await getAccount(connection, voterTokenAccount);
+
+      await voteBuilder(
+        voterWallet,
+        voterTokenAccount,
+        stakedProposal.proposalId,
+        YES_CHOICE,
+        0,
+        stakePositionPdaFor(voterWallet.publicKey)
+      ).rpc();
+
+      const after = await getAccount(connection, voterTokenAccount);
+      expect(after.amount).to.equal(before.amount);
+
+      const record = await programAccounts.voteRecord.fetch(
+        voteRecordPdaFor(stakedProposal.proposalId, voterWallet.publicKey)
+      );
+      expect(Number(record.tokensLocked)).to.equal(0);
+      expect(Number(record.tokens)).to.equal(STAKE_AMOUNT_BASE);
+      expect(Number(record.weight)).to.be.greaterThan(0);
+      expect(Number(record.weight)).to.be.lessThan(STAKE_AMOUNT_BASE);
+
+      const proposal = await programAccounts.proposal.fetch(stakedProposal.proposalPda);
+      expect(Number(proposal.totalTokens)).to.equal(STAKE_AMOUNT_BASE);
+      expect(Number(proposal.totalWeight)).to.equal(Number(record.weight));
+    });
+
+    it("ignores stake added after the proposal snapshot", async () => {
+      const lateProposal = await createProposal(
+        creatorWallet,
+        creatorTokenAccount,
+        60,
+        "Late stake proposal"
+      );
+      await stakeBuilder(
+        strangerWallet,
+        strangerTokenAccount,
+        STAKE_AMOUNT_BASE,
+        MAX_LOCK_DURATION_SEC
+      ).rpc();
+
+      await expectTxFailure(
+        voteBuilder(
+          strangerWallet,
+          strangerTokenAccount,
+          lateProposal.proposalId,
+          YES_CHOICE,
+          0,
+          stakePositionPdaFor(strangerWallet.publicKey)
+        ).rpc(),
+        "NoStakeAtSnapshot"
+      );
+    });
+
+    it("only counts staked power on snapshot-voting proposals", async () => {
+      const setSnapshotVoting = (snapshotVoting: boolean) =>
+        program.methods
+          .updateGovernanceConfig(governanceParams({ snapshotVoting }))
+          .accounts({
+            realm: realmPda,
+            authority: adminWallet.publicKey,
+          })
+          .rpc();
+
+      await setSnapshotVoting(true);
+      const snapshotProposal = await createProposal(
+        creatorWallet,
+        creatorTokenAccount,
+        60,
+        "Snapshot voting proposal"
+      );
+      await setSnapshotVoting(false);
+
+      const proposal = await programAccounts.proposal.fetch(
+        snapshotProposal.proposalPda
+      );
+      expect(proposal.snapshotVoting).to.equal(true);
+
+      await expectTxFailure(
+        voteBuilder(
+          voterWallet,
+          voterTokenAccount,
+          snapshotProposal.proposalId,
+          YES_CHOICE,
+          VOTE_STAKE_BASE,
+          stakePositionPdaFor(voterWallet.publicKey)
+        ).rpc(),
+        "SnapshotVotingRequiresStake"
+      );
+
+      await voteBuilder(
+        voterWallet,
+        voterTokenAccount,
+        snapshotProposal.proposalId,
+        YES_CHOICE,
+        0,
+        stakePositionPdaFor(voterWallet.publicKey)
+      ).rpc();
+    });
+
+    it("keeps staked tokens locked until the lock expires", async () => {
+      await expectTxFailure(
+        unstakeBuilder(voterWallet, voterTokenAccount).rpc(),
+        "StakeStillLocked"
+      );
+    });
+  });
+
+  describe("Quadratic Voting", () => {
+    it("counts the square root of committed tokens", async () => {
+      const setVotingMode = (votingMode: object) =>
+        program.methods
+          .updateGovernanceConfig(governanceParams({ votingMode }))
+          .accounts({
+            realm: realmPda,
+            authority: adminWallet.publicKey,
+          })
+          .rpc();
+
+      await setVotingMode({ quadratic: {} });
+      const quadraticProposal = await createProposal(
+        creatorWallet,
+        creatorTokenAccount,
+        60,
+        "Quadratic proposal"
+      );
+      await setVotingMode({ tokenWeighted: {} });
+
+      await voteBuilder(
+        voterWallet,
+        voterTokenAccount,
+        quadraticProposal.proposalId,
+        YES_CHOICE,
+        1_000_000
+      ).rpc();
+
+      const proposal = await programAccounts.proposal.fetch(
+        quadraticProposal.proposalPda
+      );
+      expect(proposal.votingMode).to.have.property("quadratic");
+      expect(Number(proposal.totalWeight)).to.equal(1_000);
+      expect(Number(proposal.totalTokens)).to.equal(1_000_000);
+
+      const record = await programAccounts.voteRecord.fetch(
+        voteRecordPdaFor(quadraticProposal.proposalId, voterWallet.publicKey)
+      );
+      expect(Number(record.weight)).to.equal(1_000);
+      expect(Number(record.tokens)).to.equal(1_000_000);
+      expect(Number(record.tokensLocked)).to.equal(1_000_000);
+    });
+  });
+
+  describe("Voting Rounds", () => {
+    let roundId = 0;
+    let roundPda: anchor.web3.PublicKey;
+    let roundEndsAt = 0;
+    let roundProposalIds: number[] = [];
+
+    const finalizeRoundBuilder = (proposalIds: number[]) =>
+      program.methods
+        .finalizeRound(new anchor.BN(roundId))
+        .accounts({
+          realm: realmPda,
+          votingRound: roundPda,
+          authority: strangerWallet.publicKey,
+        })
+        .signers([strangerWallet])
+        .remainingAccounts(
+          proposalIds.map((proposalId) => ({
+            pubkey: proposalPdaFor(proposalId),
+            isSigner: false,
+            isWritable: false,
+          }))
+        );
+
+    const pickRoundProposalBuilder = (proposalId: number) =>
+      program.methods
+        .pickWinner(new anchor.BN(proposalId))
+        .accounts({
+          realm: realmPda,
+          authority: adminWallet.publicKey,
+          proposalAccount: proposalPdaFor(proposalId),
+          votingRound: roundPda,
+          runoffRound: null,
+        });
+
+    it("registers proposals into an open voting round", async () => {
+      const counter = await programAccounts.proposalCounter.fetch(proposalCounterPda);
+      roundId = Number(counter.roundCount);
+      roundPda = votingRoundPdaFor(roundId);
+      const now = await getBlockTime(connection);
+      roundEndsAt = now + 20;
+
+      await program.methods
+        .createVotingRound(
+          new anchor.BN(now - 1),
+          new anchor.BN(roundEndsAt),
+          false
+        )
+        .accounts({
+          realm: realmPda,
+          proposalCounterAccount: proposalCounterPda,
+          votingRound: roundPda,
+          authority: adminWallet.publicKey,
+        })
+        .rpc();
+
+      const first = await createProposal(
+        creatorWallet,
+        creatorTokenAccount,
+        4,
+        "Round entry A",
+        PROPOSAL_STAKE_BASE,
+        DEFAULT_OPTIONS,
+        true,
+        roundPda
+      );
+      const second = await createProposal(
+        creatorWallet,
+        creatorTokenAccount,
+        4,
+        "Round entry B",
+        PROPOSAL_STAKE_BASE,
+        DEFAULT_OPTIONS,
+        true,
+        roundPda
+      );
+      roundProposalIds = [first.proposalId, second.proposalId];
+
+      await voteBuilder(
+        voterWallet,
+        voterTokenAccount,
+        second.proposalId,
+        YES_CHOICE,
+        VOTE_STAKE_BASE
+      ).rpc();
+
+      const round = await programAccounts.votingRound.fetch(roundPda);
+      expect(round.proposalIds.map(Number)).to.deep.equal(roundProposalIds);
+      const proposal = await programAccounts.proposal.fetch(first.proposalPda);
+      expect(Number(proposal.roundId)).to.equal(roundId);
+    });
+
+    it("rejects round entries the round authority has not approved", async () => {
+      const counter = await programAccounts.proposalCounter.fetch(proposalCounterPda);
+      const proposalId = Number(counter.proposalCount);
+      const deadlineTs = (await getBlockTime(connection)) + 4;
+
+      await expectTxFailure(
+        program.methods
+          .registerProposal(
+            "Unapproved round entry",
+            DEFAULT_OPTIONS,
+            new anchor.BN(deadlineTs),
+            new anchor.BN(PROPOSAL_STAKE_BASE)
+          )
+          .accounts({
+            realm: realmPda,
+            authority: strangerWallet.publicKey,
+            proposalAccount: proposalPdaFor(proposalId),
+            proposalCounterAccount: proposalCounterPda,
+            xMint: xMintPda,
+            proposalTokenAccount: strangerTokenAccount,
+            proposalVault: proposalVaultPdaFor(proposalId),
+            votingRound: roundPda,
+            roundAuthority: null,
+          })
+          .signers([strangerWallet])
+          .rpc(),
+        "UnauthorizedAccess"
+      );
+    });
+
+    it("rejects finalizing a voting round before it ends", async () => {
+      await expectTxFailure(
+        finalizeRoundBuilder(roundProposalIds).rpc(),
+        "VotingRoundStillOpen"
+      );
+    });
+
+    it("rejects proposals that are not part of the round", async () => {
+      while ((await getBlockTime(connection)) <= roundEndsAt) {
+        await sleep(500);
+      }
+
+      await expectTxFailure(
+        finalizeRoundBuilder([roundProposalIds[0], votedProposalId]).rpc(),
+        "ProposalNotInRound"
+      );
+    });
+
+    it("rejects finalizing a round without all of its proposals", async () => {
+      await expectTxFailure(
+        finalizeRoundBuilder([roundProposalIds[1]]).rpc(),
+        "IncompleteRoundProposals"
+      );
+    });
+
+    it("refuses to resolve a round proposal before the round is finalized", async () => {
+      await expectTxFailure(
+        pickRoundProposalBuilder(roundProposalIds[1]).rpc(),
+        "RoundNotFinalized"
+      );
+    });
+
+    it("rejects registering into a round that has ended", async () => {
+      await expectTxFailure(
+        createProposal(
+          creatorWallet,
+          creatorTokenAccount,
+          4,
+          "Too late for the round",
+          PROPOSAL_STAKE_BASE,
+          DEFAULT_OPTIONS,
+          false,
+          roundPda
+        ),
+        "VotingRoundClosed"
+      );
+    });
+
+    it("lets anyone finalize the round with its heaviest proposal", async () => {
+      await finalizeRoundBuilder(roundProposalIds).rpc();
+
+      const round = await programAccounts.votingRound.fetch(roundPda);
+      expect(Number(round.winningProposalId)).to.equal(roundProposalIds[1]);
+      expect(Number(round.winningWeight)).to.equal(VOTE_STAKE_BASE);
+      expect(round.finalizedAt).to.not.be.null;
+
+      await expectTxFailure(
+        finalizeRoundBuilder(roundProposalIds).rpc(),
+        "VotingRoundAlreadyFinalized"
+      );
+
+      await pickRoundProposalBuilder(roundProposalIds[1]).rpc();
+      const resolved = await programAccounts.proposal.fetch(
+        proposalPdaFor(roundProposalIds[1])
+      );
+      expect(resolved.state).to.deep.equal({ succeeded: {} });
+    });
+
+    it("defeats round proposals that did not win the round", async () => {
+      await pickRoundProposalBuilder(roundProposalIds[0]).rpc();
+
+      const proposal = await programAccounts.proposal.fetch(
+        proposalPdaFor(roundProposalIds[0])
+      );
+      expect(proposal.state).to.deep.equal({ defeated: {} });
+      expect(proposal.defeatReason).to.deep.equal({ lostRound: {} });
+    });
+  });
+
+  describe("Ranked-Choice Rounds", () => {
+    let roundId = 0;
+    let roundPda: anchor.web3.PublicKey;
+    let roundEndsAt = 0;
+    let roundProposalIds: number[] = [];
+
+    const ballotPdaFor = (voter: anchor.web3.PublicKey) =>
+      findPda(program.programId, [
+        Buffer.from(SEEDS.RANKED_BALLOT),
+        realmPda.toBuffer(),
+        proposalIdSeed(roundId),
+        voter.toBuffer(),
+      ]);
+
+    const rankedTallyPda = () =>
+      findPda(program.programId, [
+        Buffer.from(SEEDS.RANKED_TALLY),
+        realmPda.toBuffer(),
+        proposalIdSeed(roundId),
+      ]);
+
+    const submitBallotBuilder = (voter: anchor.web3.Keypair, rankings: number[]) =>
+      program.methods
+        .submitRankedBallot(
+          new anchor.BN(roundId),
+          rankings.map((proposalId) => new anchor.BN(proposalId))
+        )
+        .accounts({
+          realm: realmPda,
+          votingRound: roundPda,
+          stakePosition: stakePositionPdaFor(voter.publicKey),
+          authority: voter.publicKey,
+        })
+        .signers([voter]);
+
+    const tallyBuilder = () =>
+      program.methods
+        .tallyRankedBallots(new anchor.BN(roundId))
+        .accounts({
+          realm: realmPda,
+          votingRound: roundPda,
+          authority: strangerWallet.publicKey,
+        })
+        .signers([strangerWallet])
+        .remainingAccounts(
+          [voterWallet, strangerWallet].map((voter) => ({
+            pubkey: ballotPdaFor(voter.publicKey),
+            isSigner: false,
+            isWritable: true,
+          }))
+        );
+
+    it("records ranked ballots weighted by stake", async () => {
+      const counter = await programAccounts.proposalCounter.fetch(proposalCounterPda);
+      roundId = Number(counter.roundCount);
+      roundPda = votingRoundPdaFor(roundId);
+      const now = await getBlockTime(connection);
+      roundEndsAt = now + 20;
+
+      await program.methods
+        .createVotingRound(
+          new anchor.BN(now - 1),
+          new anchor.BN(roundEndsAt),
+          true
+        )
+        .accounts({
+          realm: realmPda,
+          proposalCounterAccount: proposalCounterPda,
+          votingRound: roundPda,
+          authority: adminWallet.publicKey,
+        })
+        .rpc();
+
+      roundProposalIds = [];
+      for (const label of ["Ranked entry A", "Ranked entry B", "Ranked entry C"]) {
+        const proposal = await createProposal(
+          creatorWallet,
+          creatorTokenAccount,
+          4,
+          label,
+          PROPOSAL_STAKE_BASE,
+          DEFAULT_OPTIONS,
+          true,
+          roundPda
+        );
+        roundProposalIds.push(proposal.proposalId);
+      }
+      const [a, , c] = roundProposalIds;
+
+      await expectTxFailure(
+        submitBallotBuilder(voterWallet, [a, votedProposalId]).rpc(),
+        "InvalidRanking"
+      );
+
+      await submitBallotBuilder(voterWallet, [a, c]).rpc();
+      await submitBallotBuilder(strangerWallet, [c, a]).rpc();
+
+      const round = await programAccounts.votingRound.fetch(roundPda);
+      expect(round.ballotCount).to.equal(2);
+      const ballot = await programAccounts.rankedBallot.fetch(
+        ballotPdaFor(voterWallet.publicKey)
+      );
+      expect(ballot.rankings.map(Number)).to.deep.equal([a, c]);
+      expect(Number(ballot.weight)).to.be.greaterThan(0);
+    });
+
+    it("rejects first-past-the-post finalization of a ranked round", async () => {
+      while ((await getBlockTime(connection)) <= roundEndsAt) {
+        await sleep(500);
+      }
+
+      await expectTxFailure(
+        program.methods
+          .finalizeRound(new anchor.BN(roundId))
+          .accounts({
+            realm: realmPda,
+            votingRound: roundPda,
+            authority: strangerWallet.publicKey,
+          })
+          .signers([strangerWallet])
+          .remainingAccounts(
+            roundProposalIds.map((proposalId) => ({
+              pubkey: proposalPdaFor(proposalId),
+              isSigner: false,
+              isWritable: false,
+            }))
+          )
+          .rpc(),
+        "RankedChoiceRound"
+      );
+    });
+
+    it("eliminates candidates pass by pass and settles a final tie by policy", async () => {
+      const [a, b, c] = roundProposalIds;
+
+      await program.methods
+        .startRankedTally(new anchor.BN(roundId))
+        .accounts({
+          realm: realmPda,
+          votingRound: roundPda,
+          authority: strangerWallet.publicKey,
+        })
+        .signers([strangerWallet])
+        .remainingAccounts(
+          roundProposalIds.map((proposalId) => ({
+            pubkey: proposalPdaFor(proposalId),
+            isSigner: false,
+            isWritable: false,
+          }))
+        )
+        .rpc();
+
+      await tallyBuilder().rpc();
+      const tally = await programAccounts.rankedTally.fetch(rankedTallyPda());
+      expect(tally.pass).to.equal(2);
+      expect(tally.candidates.map(Number)).to.deep.equal([a, c]);
+
+      await tallyBuilder().rpc();
+      const round = await programAccounts.votingRound.fetch(roundPda);
+      expect(round.finalizedAt).to.not.be.null;
+      expect(Number(round.winningProposalId)).to.not.equal(b);
+
+      const ballots = await Promise.all(
+        [voterWallet, strangerWallet].map((voter) =>
+          programAccounts.rankedBallot.fetch(ballotPdaFor(voter.publicKey))
+        )
+      );
+      const [aWeight, cWeight] = ballots.map((ballot) => Number(ballot.weight));
+      if (aWeight === cWeight) {
+        // Equal stakes leave A and C tied; the default policy picks the earliest.
+        expect(Number(round.winningProposalId)).to.equal(a);
+        expect(Number(round.winningWeight)).to.equal(aWeight);
+      } else {
+        expect(Number(round.winningProposalId)).to.equal(aWeight > cWeight ? a : c);
+        expect(Number(round.winningWeight)).to.equal(Math.max(aWeight, cWeight));
+      }
+    });
+  });
+
+  describe("Runoff Rounds", () => {
+    let roundId = 0;
+    let roundPda: anchor.web3.PublicKey;
+    let runoffRoundId = 0;
+    let runoffRoundPda: anchor.web3.PublicKey;
+    let runoffEndsAt = 0;
+    let tiedProposalIds: number[] = [];
+
+    const proposalAccountMetas = (proposalIds: number[]) =>
+      proposalIds.map((proposalId) => ({
+        pubkey: proposalPdaFor(proposalId),
+        isSigner: false,
+        isWritable: false,
+      }));
+
+    const runoffBallotPda = () =>
+      findPda(program.programId, [
+        Buffer.from(SEEDS.RANKED_BALLOT),
+        realmPda.toBuffer(),
+        proposalIdSeed(runoffRoundId),
+        voterWallet.publicKey.toBuffer(),
+      ]);
+
+    const pickTiedProposalBuilder = (
+      proposalId: number,
+      runoffRound: anchor.web3.PublicKey | null
+    ) =>
+      program.methods
+        .pickWinner(new anchor.BN(proposalId))
+        .accounts({
+          realm: realmPda,
+          authority: adminWallet.publicKey,
+          proposalAccount: proposalPdaFor(proposalId),
+          votingRound: roundPda,
+          runoffRound,
+        });
+
+    it("records the tied proposals of a round under the runoff policy", async () => {
+      const updateTiePolicy = (tiePolicy: Record<string, unknown>) =>
+        program.methods
+          .updateGovernanceConfig(governanceParams({ tiePolicy }))
+          .accounts({
+            realm: realmPda,
+            authority: adminWallet.publicKey,
+          })
+          .rpc();
+
+      const counter = await programAccounts.proposalCounter.fetch(proposalCounterPda);
+      roundId = Number(counter.roundCount);
+      roundPda = votingRoundPdaFor(roundId);
+      const now = await getBlockTime(connection);
+      const roundEndsAt = now + 12;
+
+      await updateTiePolicy({ runoff: {} });
+      await program.methods
+        .createVotingRound(new anchor.BN(now - 1), new anchor.BN(roundEndsAt), false)
+        .accounts({
+          realm: realmPda,
+          proposalCounterAccount: proposalCounterPda,
+          votingRound: roundPda,
+          authority: adminWallet.publicKey,
+        })
+        .rpc();
+      await updateTiePolicy({ earliestCreated: {} });
+
+      tiedProposalIds = [];
+      for (const [label, voter, tokenAccount] of [
+        ["Runoff entry A", voterWallet, voterTokenAccount],
+        ["Runoff entry B", strangerWallet, strangerTokenAccount],
+      ] as const) {
+        const proposal = await createProposal(
+          creatorWallet,
+          creatorTokenAccount,
+          4,
+          label,
+          PROPOSAL_STAKE_BASE,
+          DEFAULT_OPTIONS,
+          true,
+          roundPda
+        );
+        await voteBuilder(
+          voter,
+          tokenAccount,
+          proposal.proposalId,
+          YES_CHOICE,
+          VOTE_STAKE_BASE
+        ).rpc();
+        tiedProposalIds.push(proposal.proposalId);
+      }
+
+      while ((await getBlockTime(connection)) <= roundEndsAt) {
+        await sleep(500);
+      }
+
+      await program.methods
+        .finalizeRound(new anchor.BN(roundId))
+        .accounts({
+          realm: realmPda,
+          votingRound: roundPda,
+          authority: strangerWallet.publicKey,
+        })
+        .signers([strangerWallet])
+        .remainingAccounts(proposalAccountMetas(tiedProposalIds))
+        .rpc();
+
+      const round = await programAccounts.votingRound.fetch(roundPda);
+      expect(round.winningProposalId).to.be.null;
+      expect(round.tiedProposalIds.map(Number)).to.deep.equal(tiedProposalIds);
+
+      await expectTxFailure(
+        pickTiedProposalBuilder(tiedProposalIds[0], null).rpc(),
+        "RunoffPending"
+      );
+    });
+
+    it("opens a ranked runoff round between the tied proposals", async () => {
+      const counter = await programAccounts.proposalCounter.fetch(proposalCounterPda);
+      runoffRoundId = Number(counter.roundCount);
+      runoffRoundPda = votingRoundPdaFor(runoffRoundId);
+      runoffEndsAt = (await getBlockTime(connection)) + 6;
+
+      const createRunoffBuilder = (authority: anchor.web3.Keypair) =>
+        program.methods
+          .createRunoffRound(new anchor.BN(roundId), new anchor.BN(runoffEndsAt))
+          .accounts({
+            realm: realmPda,
+            proposalCounterAccount: proposalCounterPda,
+            tiedRound: roundPda,
+            runoffRound: runoffRoundPda,
+            authority: authority.publicKey,
+          })
+          .signers([authority]);
+
+      await expectTxFailure(
+        createRunoffBuilder(strangerWallet).rpc(),
+        "UnauthorizedAccess"
+      );
+      await createRunoffBuilder(adminWallet).rpc();
+
+      const runoff = await programAccounts.votingRound.fetch(runoffRoundPda);
+      expect(runoff.proposalIds.map(Number)).to.deep.equal(tiedProposalIds);
+      expect(runoff.rankedChoice).to.equal(true);
+      expect(Number(runoff.runoffOf)).to.equal(roundId);
+      const round = await programAccounts.votingRound.fetch(roundPda);
+      expect(Number(round.runoffRoundId)).to.equal(runoffRoundId);
+
+      await program.methods
+        .submitRankedBallot(
+          new anchor.BN(runoffRoundId),
+          [tiedProposalIds[1], tiedProposalIds[0]].map((id) => new anchor.BN(id))
+        )
+        .accounts({
+          realm: realmPda,
+          votingRound: runoffRoundPda,
+          stakePosition: stakePositionPdaFor(voterWallet.publicKey),
+          authority: voterWallet.publicKey,
+        })
+        .signers([voterWallet])
+        .rpc();
+    });
+
+    it("resolves the tied proposals by the runoff round's winner", async () => {
+      const [a, b] = tiedProposalIds;
+      while ((await getBlockTime(connection)) <= runoffEndsAt) {
+        await sleep(500);
+      }
+
+      await program.methods
+        .startRankedTally(new anchor.BN(runoffRoundId))
+        .accounts({
+          realm: realmPda,
+          votingRound: runoffRoundPda,
+          authority: strangerWallet.publicKey,
+        })
+        .signers([strangerWallet])
+        .remainingAccounts(proposalAccountMetas(tiedProposalIds))
+        .rpc();
+      await program.methods
+        .tallyRankedBallots(new anchor.BN(runoffRoundId))
+        .accounts({
+          realm: realmPda,
+          votingRound: runoffRoundPda,
+          authority: strangerWallet.publicKey,
+        })
+        .signers([strangerWallet])
+        .remainingAccounts([
+          { pubkey: runoffBallotPda(), isSigner: false, isWritable: true },
+        ])
+        .rpc();
+
+      const runoff = await programAccounts.votingRound.fetch(runoffRoundPda);
+      expect(Number(runoff.winningProposalId)).to.equal(b);
+
+      await pickTiedProposalBuilder(b, runoffRoundPda).rpc();
+      await pickTiedProposalBuilder(a, runoffRoundPda).rpc();
+
+      const winner = await programAccounts.proposal.fetch(proposalPdaFor(b));
+      expect(winner.state).to.deep.equal({ succeeded: {} });
+      const loser = await programAccounts.proposal.fetch(proposalPdaFor(a));
+      expect(loser.state).to.deep.equal({ defeated: {} });
+      expect(loser.defeatReason).to.deep.equal({ lostRound: {} });
+    });
+  });
+
+  describe("Conviction Voting", () => {
+    const HALF_LIFE_SEC = 3;
+    const REQUESTED_LAMPORTS = ONE_SOL / 100;
+    const SUPPORT_AMOUNT_BASE = 1_000_000;
+    let proposalId = 0;
+    let proposalPda: anchor.web3.PublicKey;
+
+    const convictionProposalPdaFor = (id: number) =>
+      findPda(program.programId, [
+        Buffer.from(SEEDS.CONVICTION_PROPOSAL),
+        realmPda.toBuffer(),
+        proposalIdSeed(id),
+      ]);
+
+    const convictionSupportPdaFor = (id: number, supporter: anchor.web3.PublicKey) =>
+      findPda(program.programId, [
+        Buffer.from(SEEDS.CONVICTION_SUPPORT),
+        realmPda.toBuffer(),
+        proposalIdSeed(id),
+        supporter.toBuffer(),
+      ]);
+
+    const createConvictionProposalBuilder = async () => {
+      const counter = await programAccounts.proposalCounter.fetch(proposalCounterPda);
+      proposalId = Number(counter.convictionCount);
+      proposalPda = convictionProposalPdaFor(proposalId);
+      return program.methods
+        .createConvictionProposal(
+          "Fund the grants pool",
+          creatorWallet.publicKey,
+          new anchor.BN(REQUESTED_LAMPORTS)
+        )
+        .accounts({
+          realm: realmPda,
+          proposalCounterAccount: proposalCounterPda,
+          convictionProposal: proposalPda,
+          authority: creatorWallet.publicKey,
+        })
+        .signers([creatorWallet]);
+    };
+
+    const executeBuilder = () =>
+      program.methods
+        .executeConvictionProposal(new anchor.BN(proposalId))
+        .accounts({
+          realm: realmPda,
+          convictionProposal: proposalPda,
+          beneficiary: creatorWallet.publicKey,
+          authority: strangerWallet.publicKey,
+        })
+        .signers([strangerWallet]);
+
+    it("rejects conviction proposals while the DAO has not enabled them", async () => {
+      await expectTxFailure(
+        (await createConvictionProposalBuilder()).rpc(),
+        "ConvictionVotingDisabled"
+      );
+    });
+
+    it("rejects enabling conviction voting without a conviction weight", async () => {
+      await expectTxFailure(
+        program.methods
+          .updateGovernanceConfig(
+            governanceParams({
+              convictionHalfLife: new anchor.BN(HALF_LIFE_SEC),
+              convictionWeightBps: 0,
+              convictionMaxRatioBps: 5_000,
+            })
+          )
+          .accounts({
+            realm: realmPda,
+            authority: adminWallet.publicKey,
+          })
+          .rpc(),
+        "InvalidGovernanceConfig"
+      );
+    });
+
+    it("builds conviction from staked support over time", async () => {
+      await program.methods
+        .updateGovernanceConfig(
+          governanceParams({
+            convictionHalfLife: new anchor.BN(HALF_LIFE_SEC),
+            convictionWeightBps: 1,
+            convictionMaxRatioBps: 5_000,
+          })
+        )
+        .accounts({
+          realm: realmPda,
+          authority: adminWallet.publicKey,
+        })
+        .rpc();
+
+      await (await createConvictionProposalBuilder()).rpc();
+
+      await program.methods
+        .supportConvictionProposal(
+          new anchor.BN(proposalId),
+          new anchor.BN(SUPPORT_AMOUNT_BASE)
+        )
+        .accounts({
+          realm: realmPda,
+          convictionProposal: proposalPda,
+          stakePosition: stakePositionPdaFor(voterWallet.publicKey),
+          authority: voterWallet.publicKey,
+        })
+        .signers([voterWallet])
+        .rpc();
+
+      const proposal = await programAccounts.convictionProposal.fetch(proposalPda);
+      expect(Number(proposal.stakedTokens)).to.equal(SUPPORT_AMOUNT_BASE);
+      const position = await programAccounts.stakePosition.fetch(
+        stakePositionPdaFor(voterWallet.publicKey)
+      );
+      expect(Number(position.convictionAllocated)).to.equal(SUPPORT_AMOUNT_BASE);
+
+      await expectTxFailure(
+        executeBuilder().rpc(),
+        "ConvictionThresholdNotReached"
+      );
+    });
+
+    it("pays out from the SOL vault once conviction crosses the threshold", async () => {
+      await sleep(4 * HALF_LIFE_SEC * 1_000);
+      const before = await connection.getBalance(creatorWallet.publicKey);
+
+      await executeBuilder().rpc();
+
+      const after = await connection.getBalance(creatorWallet.publicKey);
+      expect(after - before).to.equal(REQUESTED_LAMPORTS);
+      const proposal = await programAccounts.convictionProposal.fetch(proposalPda);
+      expect(proposal.executedAt).to.not.be.null;
+
+      await expectTxFailure(
+        executeBuilder().rpc(),
+        "ConvictionProposalAlreadyExecuted"
+      );
+    });
+
+    it("releases the stake allocation when support is withdrawn", async () => {
+      await program.methods
+        .withdrawConvictionSupport(
+          new anchor.BN(proposalId),
+          new anchor.BN(SUPPORT_AMOUNT_BASE)
+        )
+        .accounts({
+          realm: realmPda,
+          convictionProposal: proposalPda,
+          stakePosition: stakePositionPdaFor(voterWallet.publicKey),
+          authority: voterWallet.publicKey,
+        })
+        .signers([voterWallet])
+        .rpc();
+
+      const position = await programAccounts.stakePosition.fetch(
+        stakePositionPdaFor(voterWallet.publicKey)
+      );
+      expect(Number(position.convictionAllocated)).to.equal(0);
+      expect(
+        await connection.getAccountInfo(
+          convictionSupportPdaFor(proposalId, voterWallet.publicKey)
+        )
+      ).to.be.null;
+    });
+  });
+
+  describe("Commit-Reveal Voting", () => {
+    const REVEAL_PERIOD_SEC = 6;
+    const COMMIT_BOND_BASE = 500;
+    const salt = Buffer.alloc(32, 7);
+    let proposalId = 0;
+    let proposalPda: anchor.web3.PublicKey;
+    let deadlineTs = 0;
+
+    const commitmentPdaFor = (id: number, voter: anchor.web3.PublicKey) =>
+      findPda(program.programId, [
+        Buffer.from(SEEDS.COMMITMENT),
+        realmPda.toBuffer(),
+        proposalIdSeed(id),
+        voter.toBuffer(),
+      ]);
+
+    const commitmentHash = (
+      voter: anchor.web3.PublicKey,
+      choice: number,
+      tokens: number,
+      saltBytes: Buffer
+    ) => {
+      const id = Buffer.alloc(8);
+      id.writeBigUInt64LE(BigInt(proposalId));
+      const amount = Buffer.alloc(8);
+      amount.writeBigUInt64LE(BigInt(tokens));
+      return createHash("sha256")
+        .update(
+          Buffer.concat([id, voter.toBuffer(), Buffer.from([choice]), amount, saltBytes])
+        )
+        .digest();
+    };
+
+    const revealBuilder = (saltBytes: Buffer) =>
+      program.methods
+        .revealVote(
+          new anchor.BN(proposalId),
+          YES_CHOICE,
+          new anchor.BN(VOTE_STAKE_BASE),
+          [...saltBytes]
+        )
+        .accounts({
+          realm: realmPda,
+          voterAccount: voterPdaFor(voterWallet.publicKey),
+          commitment: commitmentPdaFor(proposalId, voterWallet.publicKey),
+          voteRecord: voteRecordPdaFor(proposalId, voterWallet.publicKey),
+          xMint: xMintPda,
+          voterTokenAccount,
+          proposalVault: proposalVaultPdaFor(proposalId),
+          proposalAccount: proposalPda,
+          authority: voterWallet.publicKey,
+        })
+        .signers([voterWallet]);
+
+    before(async () => {
+      await program.methods
+        .updateGovernanceConfig(
+          governanceParams({
+            commitReveal: true,
+            revealPeriod: new anchor.BN(REVEAL_PERIOD_SEC),
+            commitBond: new anchor.BN(COMMIT_BOND_BASE),
+          })
+        )
+        .accounts({
+          realm: realmPda,
+          authority: adminWallet.publicKey,
+        })
+        .rpc();
+
+      ({ proposalId, proposalPda, deadlineTs } = await createProposal(
+        creatorWallet,
+        creatorTokenAccount,
+        6,
+        "Sealed-ballot proposal"
+      ));
+
+      await program.methods
+        .updateGovernanceConfig(governanceParams())
+        .accounts({
+          realm: realmPda,
+          authority: adminWallet.publicKey,
+        })
+        .rpc();
+    });
+
+    it("rejects open votes on commit-reveal proposals", async () => {
+      const proposal = await programAccounts.proposal.fetch(proposalPda);
+      expect(Number(proposal.revealEndsAt)).to.equal(deadlineTs + REVEAL_PERIOD_SEC);
+
+      await expectTxFailure(
+        voteBuilder(voterWallet, voterTokenAccount, proposalId, YES_CHOICE, VOTE_STAKE_BASE).rpc(),
+        "CommitRevealRequired"
+      );
+    });
+
+    it("escrows the bond when a vote is committed", async () => {
+      await program.methods
+        .commitVote(new anchor.BN(proposalId), [
+          ...commitmentHash(voterWallet.publicKey, YES_CHOICE, VOTE_STAKE_BASE, salt),
+        ])
+        .accounts({
+          realm: realmPda,
+          voterAccount: voterPdaFor(voterWallet.publicKey),
+          commitment: commitmentPdaFor(proposalId, voterWallet.publicKey),
+          xMint: xMintPda,
+          voterTokenAccount,
+          proposalVault: proposalVaultPdaFor(proposalId),
+          proposalAccount: proposalPda,
+          authority: voterWallet.publicKey,
+        })
+        .signers([voterWallet])
+        .rpc();
+
+      const commitment = await programAccounts.voteCommitment.fetch(
+        commitmentPdaFor(proposalId, voterWallet.publicKey)
+      );
+      expect(Number(commitment.bond)).to.equal(COMMIT_BOND_BASE);
+      expect(commitment.revealed).to.equal(false);
+
+      const proposal = await programAccounts.proposal.fetch(proposalPda);
+      expect(Number(proposal.unrevealedCommitments)).to.equal(1);
+      expect(Number(proposal.totalWeight)).to.equal(0);
+    });
+
+    it("counts the vote only once it is revealed with the committed salt", async () => {
+      while ((await getBlockTime(connection)) <= deadlineTs) {
+        await sleep(500);
+      }
+
+      await expectTxFailure(
+        revealBuilder(Buffer.alloc(32, 8)).rpc(),
+        "CommitmentMismatch"
+      );
+
+      await revealBuilder(salt).rpc();
+
+      const proposal = await programAccounts.proposal.fetch(proposalPda);
+      expect(Number(proposal.unrevealedCommitments)).to.equal(0);
+      expect(Number(proposal.totalWeight)).to.equal(VOTE_STAKE_BASE);
+      const voteRecord = await programAccounts.voteRecord.fetch(
+        voteRecordPdaFor(proposalId, voterWallet.publicKey)
+      );
+      expect(Number(voteRecord.tokensLocked)).to.equal(
+        VOTE_STAKE_BASE + COMMIT_BOND_BASE
+      );
+    });
+
+    it("picks the winner after the reveal window closes", async () => {
+      const pickWinnerBuilder = () =>
+        program.methods
+          .pickWinner(new anchor.BN(proposalId))
+          .accounts({
+            realm: realmPda,
+            authority: adminWallet.publicKey,
+            proposalAccount: proposalPda,
+            votingRound: null,
+            runoffRound: null,
+          });
+
+      await expectTxFailure(pickWinnerBuilder().rpc());
+
+      while ((await getBlockTime(connection)) < deadlineTs + REVEAL_PERIOD_SEC) {
+        await sleep(500);
+      }
+
+      await pickWinnerBuilder().rpc();
+
+      const proposal = await programAccounts.proposal.fetch(proposalPda);
+      expect(proposal.winningOption).to.equal(YES_CHOICE);
+    });
+  });
+
+  describe("Anti-Sniping Deadline Extension", () => {
+    const EXTENSION_SEC = 5;
+    let proposalId = 0;
+    let proposalPda: anchor.web3.PublicKey;
+    let deadlineTs = 0;
+
+    before(async () => {
+      await program.methods
+        .updateGovernanceConfig(
+          governanceParams({
+            antiSniping: {
+              window: new anchor.BN(60),
+              thresholdBps: 10_000,
+              extension: new anchor.BN(EXTENSION_SEC),
+              maxExtensions: 1,
+            },
+          })
+        )
+        .accounts({
+          realm: realmPda,
+          authority: adminWallet.publicKey,
+        })
+        .rpc();
+
+      ({ proposalId, proposalPda, deadlineTs } = await createProposal(
+        creatorWallet,
+        creatorTokenAccount,
+        20,
+        "Late-vote proposal"
+      ));
+
+      await program.methods
+        .updateGovernanceConfig(governanceParams())
+        .accounts({
+          realm: realmPda,
+          authority: adminWallet.publicKey,
+        })
+        .rpc();
+    });
+
+    it("extends the deadline when a late vote changes the leading option", async () => {
+      await voteBuilder(
+        voterWallet,
+        voterTokenAccount,
+        proposalId,
+        YES_CHOICE,
+        VOTE_STAKE_BASE
+      ).rpc();
+
+      const proposal = await programAccounts.proposal.fetch(proposalPda);
+      expect(Number(proposal.deadline)).to.equal(deadlineTs + EXTENSION_SEC);
+      expect(proposal.extensions).to.equal(1);
+    });
+
+    it("stops extending once the maximum number of extensions is reached", async () => {
+      await voteBuilder(
+        strangerWallet,
+        strangerTokenAccount,
+        proposalId,
+        YES_CHOICE + 1,
+        VOTE_STAKE_BASE
+      ).rpc();
+
+      const proposal = await programAccounts.proposal.fetch(proposalPda);
+      expect(Number(proposal.deadline)).to.equal(deadlineTs + EXTENSION_SEC);
+      expect(proposal.extensions).to.equal(1);
+    });
+
+    it("extends the deadline when a late vote moves the tally past the threshold", async () => {
+      await program.methods
+        .updateGovernanceConfig(
+          governanceParams({
+            antiSniping: {
+              window: new anchor.BN(60),
+              thresholdBps: 5_000,
+              extension: new anchor.BN(EXTENSION_SEC),
+              maxExtensions: 3,
+            },
+          })
+        )
+        .accounts({
+          realm: realmPda,
+          authority: adminWallet.publicKey,
+        })
+        .rpc();
+      const moved = await createProposal(
+        creatorWallet,
+        creatorTokenAccount,
+        20,
+        "Heavy late vote proposal"
+      );
+      const nudged = await createProposal(
+        creatorWallet,
+        creatorTokenAccount,
+        20,
+        "Light late vote proposal"
+      );
+      await program.methods
+        .updateGovernanceConfig(governanceParams())
+        .accounts({
+          realm: realmPda,
+          authority: adminWallet.publicKey,
+        })
+        .rpc();
+
+      // The first vote on an empty tally always counts as moving it.
+      await voteBuilder(
+        voterWallet,
+        voterTokenAccount,
+        moved.proposalId,
+        YES_CHOICE,
+        VOTE_STAKE_BASE
+      ).rpc();
+      let proposal = await programAccounts.proposal.fetch(moved.proposalPda);
+      expect(proposal.extensions).to.equal(1);
+
+      // Doubling the leading option's weight keeps the leader but moves the tally by 100%.
+      await voteBuilder(
+        strangerWallet,
+        strangerTokenAccount,
+        moved.proposalId,
+        YES_CHOICE,
+        VOTE_STAKE_BASE
+      ).rpc();
+      proposal = await programAccounts.proposal.fetch(moved.proposalPda);
+      expect(Number(proposal.deadline)).to.equal(moved.deadlineTs + 2 * EXTENSION_SEC);
+      expect(proposal.extensions).to.equal(2);
+
+      await voteBuilder(
+        voterWallet,
+        voterTokenAccount,
+        nudged.proposalId,
+        YES_CHOICE,
+        VOTE_STAKE_BASE
+      ).rpc();
+      // Half of the weight already cast is not more than the 50% threshold.
+      await voteBuilder(
+        strangerWallet,
+        strangerTokenAccount,
+        nudged.proposalId,
+        YES_CHOICE,
+        VOTE_STAKE_BASE / 2
+      ).rpc();
+      proposal = await programAccounts.proposal.fetch(nudged.proposalPda);
+      expect(Number(proposal.deadline)).to.equal(nudged.deadlineTs + EXTENSION_SEC);
+      expect(proposal.extensions).to.equal(1);
+    });
+
+    it("extends the deadline when a late retraction changes the leading option", async () => {
+      await program.methods
+        .updateGovernanceConfig(
+          governanceParams({
+            antiSniping: {
+              window: new anchor.BN(60),
+              thresholdBps: 10_000,
+              extension: new anchor.BN(EXTENSION_SEC),
+              maxExtensions: 3,
+            },
+          })
+        )
+        .accounts({
+          realm: realmPda,
+          authority: adminWallet.publicKey,
+        })
+        .rpc();
+      const retracted = await createProposal(
+        creatorWallet,
+        creatorTokenAccount,
+        20,
+        "Late retraction proposal"
+      );
+      await program.methods
+        .updateGovernanceConfig(governanceParams())
+        .accounts({
+          realm: realmPda,
+          authority: adminWallet.publicKey,
+        })
+        .rpc();
+
+      await voteBuilder(
+        voterWallet,
+        voterTokenAccount,
+        retracted.proposalId,
+        YES_CHOICE,
+        VOTE_STAKE_BASE
+      ).rpc();
+      await voteBuilder(
+        strangerWallet,
+        strangerTokenAccount,
+        retracted.proposalId,
+        YES_CHOICE + 1,
+        VOTE_STAKE_BASE / 2
+      ).rpc();
+      let proposal = await programAccounts.proposal.fetch(retracted.proposalPda);
+      expect(proposal.extensions).to.equal(1);
+
+      // Pulling the leading vote hands the lead to the other option.
+      await program.methods
+        .retractVote(new anchor.BN(retracted.proposalId))
+        .accounts({
+          realm: realmPda,
+          voterAccount: voterPdaFor(voterWallet.publicKey),
+          voteRecord: voteRecordPdaFor(retracted.proposalId, voterWallet.publicKey),
+          proposalAccount: retracted.proposalPda,
+          proposalVault: proposalVaultPdaFor(retracted.proposalId),
+          voterTokenAccount,
+          rentPayer: voterWallet.publicKey,
+          delegation: null,
+          authority: voterWallet.publicKey,
+        })
+        .signers([voterWallet])
+        .rpc();
+
+      proposal = await programAccounts.proposal.fetch(retracted.proposalPda);
+      expect(Number(proposal.deadline)).to.equal(
+        retracted.deadlineTs + 2 * EXTENSION_SEC
+      );
+      expect(proposal.extensions).to.equal(2);
+    });
+  });
+
+  describe("Voting Delay", () => {
+    const VOTING_DELAY_SEC = 4;
+    let proposalId = 0;
+    let proposalPda: anchor.web3.PublicKey;
+
+    before(async () => {
+      await program.methods
+        .updateGovernanceConfig(
+          governanceParams({ votingDelay: new anchor.BN(VOTING_DELAY_SEC) })
+        )
+        .accounts({
+          realm: realmPda,
+          authority: adminWallet.publicKey,
+        })
+        .rpc();
+
+      ({ proposalId, proposalPda } = await createProposal(
+        creatorWallet,
+        creatorTokenAccount,
+        30,
+        "Delayed proposal"
+      ));
+
+      await program.methods
+        .updateGovernanceConfig(governanceParams())
+        .accounts({
+          realm: realmPda,
+          authority: adminWallet.publicKey,
+        })
+        .rpc();
+    });
+
+    it("rejects votes before the voting delay has passed", async () => {
+      const proposal = await programAccounts.proposal.fetch(proposalPda);
+      expect(Number(proposal.snapshotAt)).to.equal(Number(proposal.votingStartsAt));
+
+      await expectTxFailure(
+        voteBuilder(voterWallet, voterTokenAccount, proposalId, YES_CHOICE, VOTE_STAKE_BASE).rpc(),
+        "VotingNotStarted"
+      );
+    });
+
+    it("accepts votes once voting has started", async () => {
+      const proposal = await programAccounts.proposal.fetch(proposalPda);
+      while ((await getBlockTime(connection)) < Number(proposal.votingStartsAt)) {
+        await sleep(500);
+      }
+
+      await voteBuilder(
+        voterWallet,
+        voterTokenAccount,
+        proposalId,
+        YES_CHOICE,
+        VOTE_STAKE_BASE
+      ).rpc();
+
+      const updated = await programAccounts.proposal.fetch(proposalPda);
+      expect(Number(updated.totalWeight)).to.equal(VOTE_STAKE_BASE);
+    });
+  });
+
+  describe("Winner Selection, Closing, and Withdrawals", () => {
+    it("rejects pickWinner while voting is still active", async () => {
+      const activeProposal = await createProposal(
+        creatorWallet,
+        creatorTokenAccount,
+        20,
+        "Still active proposal"
       );
 
       const tx = await program.methods
-        .pickWinner(activeProposal.proposalId)
+        .pickWinner(new anchor.BN(activeProposal.proposalId))
         .accounts({
+          realm: realmPda,
           authority: adminWallet.publicKey,
           proposalAccount: activeProposal.proposalPda,
-          winnerAccount: winnerPda,
+          votingRound: null,
+          runoffRound: null,
         })
         .transaction();
 
       await expectTxFailure(provider.simulate(tx));
     });
 
-    it("rejects pickWinner when no votes were cast", async () => {
+    it("marks proposal defeated when quorum is not reached", async () => {
       const noVoteProposal = await createProposal(
         creatorWallet,
         creatorTokenAccount,
@@ -636,16 +3190,34 @@ describe("vote_app protocol edge-case suite", () => {
         await sleep(500);
       }
 
-      const tx = await program.methods
-        .pickWinner(noVoteProposal.proposalId)
+      await program.methods
+        .pickWinner(new anchor.BN(noVoteProposal.proposalId))
         .accounts({
+          realm: realmPda,
           authority: adminWallet.publicKey,
           proposalAccount: noVoteProposal.proposalPda,
-          winnerAccount: winnerPda,
+          votingRound: null,
+          runoffRound: null,
         })
-        .transaction();
+        .rpc();
 
-      await expectTxFailure(provider.simulate(tx));
+      defeatedProposalId = noVoteProposal.proposalId;
+      const proposal = await programAccounts.proposal.fetch(
+        noVoteProposal.proposalPda
+      );
+      expect(proposal.state).to.deep.equal({ defeated: {} });
+      expect(proposal.defeatReason).to.deep.equal({ quorumNotReached: {} });
+      expect(proposal.winningOption).to.be.null;
+
+      const result = await programAccounts.proposalResult.fetch(
+        findPda(program.programId, [
+          Buffer.from(SEEDS.PROPOSAL_RESULT),
+          realmPda.toBuffer(),
+          proposalIdSeed(noVoteProposal.proposalId),
+        ])
+      );
+      expect(result.outcome).to.deep.equal({ defeated: {} });
+      expect(result.defeatReason).to.deep.equal({ quorumNotReached: {} });
     });
 
     it("picks winner successfully after deadline for voted proposal", async () => {
@@ -657,30 +3229,158 @@ describe("vote_app protocol edge-case suite", () => {
       }
 
       await program.methods
-        .pickWinner(votedProposalId)
+        .pickWinner(new anchor.BN(votedProposalId))
         .accounts({
+          realm: realmPda,
           authority: adminWallet.publicKey,
           proposalAccount: votedProposalPda,
-          winnerAccount: winnerPda,
+          votingRound: null,
+          runoffRound: null,
         })
         .rpc();
 
-      const winner = await programAccounts.winner.fetch(winnerPda);
-      expect(Number(winner.winningProposalId)).to.equal(votedProposalId);
-      expect(Number(winner.winningVotes)).to.equal(1);
+      const resolved = await programAccounts.proposal.fetch(votedProposalPda);
+      expect(resolved.state).to.deep.equal({ succeeded: {} });
+      expect(resolved.winningOption).to.equal(YES_CHOICE);
+      expect(Number(resolved.options[YES_CHOICE].votes)).to.equal(1);
+      expect(Number(resolved.options[YES_CHOICE].weight)).to.equal(
+        VOTE_STAKE_BASE
+      );
+
+      const result = await programAccounts.proposalResult.fetch(
+        findPda(program.programId, [
+          Buffer.from(SEEDS.PROPOSAL_RESULT),
+          realmPda.toBuffer(),
+          proposalIdSeed(votedProposalId),
+        ])
+      );
+      expect(result.outcome).to.deep.equal({ succeeded: {} });
+      expect(result.winningOption).to.equal(YES_CHOICE);
+      expect(Number(result.totalVotes)).to.equal(1);
+      expect(result.resolvedBy.toBase58()).to.equal(
+        adminWallet.publicKey.toBase58()
+      );
     });
 
-    it("rejects closing proposal by non-creator", async () => {
+    it("rejects declaring the same proposal's winner twice", async () => {
       await expectTxFailure(
         program.methods
-          .closeProposal(votedProposalId)
+          .pickWinner(new anchor.BN(votedProposalId))
           .accounts({
+            realm: realmPda,
+            authority: adminWallet.publicKey,
             proposalAccount: votedProposalPda,
-            destination: voterWallet.publicKey,
-            authority: voterWallet.publicKey,
+            votingRound: null,
+            runoffRound: null,
           })
-          .signers([voterWallet])
           .rpc(),
+        "ProposalAlreadyResolved"
+      );
+    });
+
+    it("defeats a tied proposal under the no-winner tie policy", async () => {
+      const updateTiePolicy = (tiePolicy: Record<string, unknown>) =>
+        program.methods
+          .updateGovernanceConfig(governanceParams({ tiePolicy }))
+          .accounts({
+            realm: realmPda,
+            authority: adminWallet.publicKey,
+          })
+          .rpc();
+
+      await updateTiePolicy({ noWinner: {} });
+      const tied = await createProposal(
+        creatorWallet,
+        creatorTokenAccount,
+        4,
+        "Evenly split proposal"
+      );
+      await updateTiePolicy({ earliestCreated: {} });
+
+      await voteBuilder(
+        voterWallet,
+        voterTokenAccount,
+        tied.proposalId,
+        YES_CHOICE,
+        VOTE_STAKE_BASE
+      ).rpc();
+      await voteBuilder(
+        strangerWallet,
+        strangerTokenAccount,
+        tied.proposalId,
+        YES_CHOICE + 1,
+        VOTE_STAKE_BASE
+      ).rpc();
+
+      while ((await getBlockTime(connection)) <= tied.deadlineTs) {
+        await sleep(500);
+      }
+
+      await program.methods
+        .pickWinner(new anchor.BN(tied.proposalId))
+        .accounts({
+          realm: realmPda,
+          authority: adminWallet.publicKey,
+          proposalAccount: tied.proposalPda,
+          votingRound: null,
+          runoffRound: null,
+        })
+        .rpc();
+
+      const proposal = await programAccounts.proposal.fetch(tied.proposalPda);
+      expect(proposal.state).to.deep.equal({ defeated: {} });
+      expect(proposal.defeatReason).to.deep.equal({ tie: {} });
+      expect(proposal.winningOption).to.be.null;
+    });
+
+    it("defeats a proposal whose options tie under the earliest-created policy", async () => {
+      const tied = await createProposal(
+        creatorWallet,
+        creatorTokenAccount,
+        4,
+        "Split approval proposal"
+      );
+
+      await voteBuilder(
+        voterWallet,
+        voterTokenAccount,
+        tied.proposalId,
+        YES_CHOICE + 1,
+        VOTE_STAKE_BASE
+      ).rpc();
+      await voteBuilder(
+        strangerWallet,
+        strangerTokenAccount,
+        tied.proposalId,
+        YES_CHOICE,
+        VOTE_STAKE_BASE
+      ).rpc();
+
+      while ((await getBlockTime(connection)) <= tied.deadlineTs) {
+        await sleep(500);
+      }
+
+      await program.methods
+        .pickWinner(new anchor.BN(tied.proposalId))
+        .accounts({
+          realm: realmPda,
+          authority: adminWallet.publicKey,
+          proposalAccount: tied.proposalPda,
+          votingRound: null,
+          runoffRound: null,
+        })
+        .rpc();
+
+      const proposal = await programAccounts.proposal.fetch(tied.proposalPda);
+      expect(proposal.tiePolicy).to.deep.equal({ earliestCreated: {} });
+      expect(proposal.state).to.deep.equal({ defeated: {} });
+      expect(proposal.defeatReason).to.deep.equal({ tie: {} });
+      expect(proposal.winningOption).to.be.null;
+    });
+
+    it("rejects closing proposal by non-creator", async () => {
+      await expectTxFailure(
+        closeProposalBuilder(votedProposalId, voterWallet).rpc(),
         "UnauthorizedAccess"
       );
     });
@@ -694,99 +3394,372 @@ describe("vote_app protocol edge-case suite", () => {
       );
 
       await expectTxFailure(
-        program.methods
-          .closeProposal(openProposal.proposalId)
-          .accounts({
-            proposalAccount: openProposal.proposalPda,
-            destination: creatorWallet.publicKey,
-            authority: creatorWallet.publicKey,
-          })
-          .signers([creatorWallet])
-          .rpc(),
+        closeProposalBuilder(openProposal.proposalId, creatorWallet).rpc(),
         "VotingStillActive"
       );
     });
 
-    it("closes ended proposal successfully", async () => {
-      const before = await connection.getAccountInfo(votedProposalPda);
-      expect(before).to.not.be.null;
+    it("rejects closing a succeeded proposal before it is executed", async () => {
+      await expectTxFailure(
+        closeProposalBuilder(votedProposalId, creatorWallet).rpc(),
+        "InvalidStateTransition"
+      );
+    });
 
+    it("executes succeeded proposal", async () => {
       await program.methods
-        .closeProposal(votedProposalId)
+        .executeProposal(new anchor.BN(votedProposalId))
         .accounts({
+          realm: realmPda,
+          authority: adminWallet.publicKey,
           proposalAccount: votedProposalPda,
-          destination: creatorWallet.publicKey,
-          authority: creatorWallet.publicKey,
+          proposalInstruction: null,
         })
-        .signers([creatorWallet])
+        .rpc();
+
+      const proposal = await programAccounts.proposal.fetch(votedProposalPda);
+      expect(proposal.state).to.deep.equal({ executed: {} });
+    });
+
+    it("rejects closing proposal while escrow still holds tokens", async () => {
+      await expectTxFailure(
+        closeProposalBuilder(votedProposalId, creatorWallet).rpc(),
+        "EscrowNotEmpty"
+      );
+    });
+
+    it("returns escrowed vote tokens and deposit after resolution", async () => {
+      const voterBefore = (await getAccount(connection, voterTokenAccount)).amount;
+      await withdrawVoteTokensBuilder(
+        voterWallet,
+        voterTokenAccount,
+        votedProposalId
+      ).rpc();
+      const voterAfter = (await getAccount(connection, voterTokenAccount)).amount;
+      expect(voterAfter - voterBefore).to.equal(BigInt(VOTE_STAKE_BASE));
+
+      await expectTxFailure(
+        withdrawVoteTokensBuilder(
+          voterWallet,
+          voterTokenAccount,
+          votedProposalId
+        ).rpc(),
+        "NothingToWithdraw"
+      );
+
+      const creatorBefore = (await getAccount(connection, creatorTokenAccount))
+        .amount;
+      await reclaimDepositBuilder(
+        creatorWallet,
+        creatorTokenAccount,
+        votedProposalId
+      ).rpc();
+      const creatorAfter = (await getAccount(connection, creatorTokenAccount))
+        .amount;
+      expect(creatorAfter - creatorBefore).to.equal(BigInt(PROPOSAL_STAKE_BASE));
+
+      const proposal = await programAccounts.proposal.fetch(votedProposalPda);
+      expect(Number(proposal.escrowedTokens)).to.equal(0);
+    });
+
+    it("slashes the deposit of a proposal that missed quorum", async () => {
+      const treasuryBefore = (await getAccount(connection, treasuryTokenAccount))
+        .amount;
+      await reclaimDepositBuilder(
+        creatorWallet,
+        creatorTokenAccount,
+        defeatedProposalId
+      ).rpc();
+      const treasuryAfter = (await getAccount(connection, treasuryTokenAccount))
+        .amount;
+
+      expect(treasuryAfter - treasuryBefore).to.equal(
+        BigInt(PROPOSAL_STAKE_BASE)
+      );
+    });
+
+    it("closes ended proposal successfully", async () => {
+      const before = await connection.getAccountInfo(votedProposalPda);
+      expect(before).to.not.be.null;
+
+      const voteRecordPda = voteRecordPdaFor(
+        votedProposalId,
+        voterWallet.publicKey
+      );
+
+      await closeProposalBuilder(votedProposalId, creatorWallet)
+        .remainingAccounts([
+          { pubkey: voteRecordPda, isSigner: false, isWritable: true },
+          { pubkey: voterWallet.publicKey, isSigner: false, isWritable: true },
+        ])
         .rpc();
 
       const after = await connection.getAccountInfo(votedProposalPda);
       expect(after).to.be.null;
+      expect(await connection.getAccountInfo(voteRecordPda)).to.be.null;
+      expect(
+        await connection.getAccountInfo(proposalVaultPdaFor(votedProposalId))
+      ).to.be.null;
     });
 
-    it("rejects SOL withdraw by non-authority", async () => {
+    it("rejects treasury token withdraw signed by a regular wallet", async () => {
       await expectTxFailure(
         program.methods
-          .withdrawSol(new anchor.BN(100_000))
+          .withdrawTreasuryTokens(new anchor.BN(1))
           .accounts({
-            authority: creatorWallet.publicKey,
+            realm: realmPda,
+            governanceAuthority: creatorWallet.publicKey,
+            recipientTokenAccount: creatorTokenAccount,
           })
           .signers([creatorWallet])
           .rpc(),
-        "UnauthorizedAccess"
+        "ConstraintSeeds"
       );
     });
 
-    it("rejects SOL withdraw larger than vault balance", async () => {
-      const vaultBalance = await connection.getBalance(solVaultPda);
-
+    it("rejects SOL withdraw signed by a regular wallet", async () => {
       await expectTxFailure(
         program.methods
-          .withdrawSol(new anchor.BN(vaultBalance + 1))
+          .withdrawSol(new anchor.BN(100_000))
           .accounts({
-            authority: adminWallet.publicKey,
+            realm: realmPda,
+            governanceAuthority: creatorWallet.publicKey,
+            recipient: creatorWallet.publicKey,
           })
-          .rpc()
+          .signers([creatorWallet])
+          .rpc(),
+        "ConstraintSeeds"
       );
     });
 
-    it("allows authorized SOL withdraw", async () => {
+    it("withdraws SOL only through executed proposals", async () => {
       const vaultBalance = await connection.getBalance(solVaultPda);
       const withdrawAmount = Math.max(1, Math.min(100_000, vaultBalance));
+
+      const governedWithdraw = async (
+        amount: number,
+        info: string,
+        choice = YES_CHOICE
+      ) => {
+        const created = await createProposal(
+          creatorWallet,
+          creatorTokenAccount,
+          8,
+          info,
+          PROPOSAL_STAKE_BASE,
+          DEFAULT_OPTIONS,
+          false
+        );
+        const withdrawIx = await program.methods
+          .withdrawSol(new anchor.BN(amount))
+          .accounts({
+            realm: realmPda,
+            governanceAuthority: governancePda,
+            recipient: adminWallet.publicKey,
+          })
+          .instruction();
+
+        await program.methods
+          .addProposalInstruction(
+            new anchor.BN(created.proposalId),
+            program.programId,
+            withdrawIx.keys,
+            withdrawIx.data
+          )
+          .accounts({
+            realm: realmPda,
+            authority: creatorWallet.publicKey,
+            proposalAccount: created.proposalPda,
+            proposalInstruction: proposalInstructionPdaFor(created.proposalId, 0),
+          })
+          .signers([creatorWallet])
+          .rpc();
+
+        await program.methods
+          .activateProposal(new anchor.BN(created.proposalId))
+          .accounts({
+            realm: realmPda,
+            authority: creatorWallet.publicKey,
+            proposalAccount: created.proposalPda,
+          })
+          .signers([creatorWallet])
+          .rpc();
+
+        await voteBuilder(
+          strangerWallet,
+          strangerTokenAccount,
+          created.proposalId,
+          choice,
+          VOTE_STAKE_BASE
+        ).rpc();
+
+        return { ...created, withdrawIx };
+      };
+
+      const executeBuilder = (
+        proposalId: number,
+        withdrawIx: anchor.web3.TransactionInstruction
+      ) =>
+        program.methods
+          .executeProposal(new anchor.BN(proposalId))
+          .accounts({
+            realm: realmPda,
+            authority: adminWallet.publicKey,
+            proposalAccount: proposalPdaFor(proposalId),
+            proposalInstruction: proposalInstructionPdaFor(proposalId, 0),
+            governanceAuthority: governancePda,
+          })
+          .remainingAccounts([
+            ...withdrawIx.keys.map((key) => ({ ...key, isSigner: false })),
+            {
+              pubkey: program.programId,
+              isSigner: false,
+              isWritable: false,
+            },
+          ]);
+
+      const overdraw = await governedWithdraw(
+        vaultBalance + 1,
+        "Withdraw more than the vault"
+      );
+      const rejected = await governedWithdraw(
+        withdrawAmount,
+        "Rejected payout",
+        YES_CHOICE + 1
+      );
+      const payout = await governedWithdraw(withdrawAmount, "Pay the admin");
+
+      await expectTxFailure(
+        executeBuilder(payout.proposalId, payout.withdrawIx).rpc(),
+        "InvalidStateTransition"
+      );
+
+      while ((await getBlockTime(connection)) <= payout.deadlineTs) {
+        await sleep(500);
+      }
+
+      for (const proposalId of [
+        overdraw.proposalId,
+        rejected.proposalId,
+        payout.proposalId,
+      ]) {
+        await program.methods
+          .pickWinner(new anchor.BN(proposalId))
+          .accounts({
+            realm: realmPda,
+            authority: adminWallet.publicKey,
+            proposalAccount: proposalPdaFor(proposalId),
+            votingRound: null,
+            runoffRound: null,
+          })
+          .rpc();
+      }
+
+      await expectTxFailure(
+        executeBuilder(overdraw.proposalId, overdraw.withdrawIx).rpc()
+      );
+
+      const rejectedProposal = await programAccounts.proposal.fetch(rejected.proposalPda);
+      expect(rejectedProposal.winningOption).to.equal(YES_CHOICE + 1);
+      await expectTxFailure(
+        executeBuilder(rejected.proposalId, rejected.withdrawIx).rpc(),
+        "ProposalNotApproved"
+      );
+
       const adminBalanceBefore = await connection.getBalance(adminWallet.publicKey);
+      await executeBuilder(payout.proposalId, payout.withdrawIx).rpc();
+      const adminBalanceAfter = await connection.getBalance(adminWallet.publicKey);
+
+      const executed = await programAccounts.proposal.fetch(payout.proposalPda);
+      expect(executed.state).to.deep.equal({ executed: {} });
+      expect(Number(executed.instructionsExecuted)).to.equal(1);
+      expect(adminBalanceAfter).to.be.greaterThan(
+        adminBalanceBefore + withdrawAmount - 100_000
+      );
+    });
+
+    it("rejects closing voter while vote tokens are still escrowed", async () => {
+      await expectTxFailure(
+        program.methods
+          .closeVoter()
+          .accounts({
+            realm: realmPda,
+            voterAccount: voterPda,
+            authority: voterWallet.publicKey,
+          })
+          .remainingAccounts([
+            {
+              pubkey: voteRecordPdaFor(
+                secondVotedProposalId,
+                voterWallet.publicKey
+              ),
+              isSigner: false,
+              isWritable: true,
+            },
+            {
+              pubkey: proposalPdaFor(secondVotedProposalId),
+              isSigner: false,
+              isWritable: false,
+            },
+          ])
+          .signers([voterWallet])
+          .rpc(),
+        "VoteTokensStillLocked"
+      );
 
       await program.methods
-        .withdrawSol(new anchor.BN(withdrawAmount))
+        .pickWinner(new anchor.BN(secondVotedProposalId))
         .accounts({
+          realm: realmPda,
           authority: adminWallet.publicKey,
+          proposalAccount: proposalPdaFor(secondVotedProposalId),
+          votingRound: null,
+          runoffRound: null,
         })
         .rpc();
-
-      const adminBalanceAfter = await connection.getBalance(adminWallet.publicKey);
-      expect(adminBalanceAfter).to.be.greaterThan(adminBalanceBefore - 100_000);
+      await withdrawVoteTokensBuilder(
+        voterWallet,
+        voterTokenAccount,
+        secondVotedProposalId
+      ).rpc();
     });
 
     it("closes voter account and rejects second close", async () => {
       const before = await connection.getAccountInfo(voterPda);
       expect(before).to.not.be.null;
 
+      const voteRecordPda = voteRecordPdaFor(
+        secondVotedProposalId,
+        voterWallet.publicKey
+      );
+
       await program.methods
         .closeVoter()
         .accounts({
+          realm: realmPda,
           voterAccount: voterPda,
           authority: voterWallet.publicKey,
         })
+        .remainingAccounts([
+          { pubkey: voteRecordPda, isSigner: false, isWritable: true },
+          {
+            pubkey: proposalPdaFor(secondVotedProposalId),
+            isSigner: false,
+            isWritable: false,
+          },
+        ])
         .signers([voterWallet])
         .rpc();
 
       const after = await connection.getAccountInfo(voterPda);
       expect(after).to.be.null;
+      expect(await connection.getAccountInfo(voteRecordPda)).to.be.null;
 
       await expectTxFailure(
         program.methods
           .closeVoter()
           .accounts({
+            realm: realmPda,
             voterAccount: voterPda,
             authority: voterWallet.publicKey,
           })